@@ -0,0 +1,43 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linux_libc_auxv::StackLayoutRef;
+
+// Only exercises the safe API surface: The pointers in the layout are never
+// dereferenced.
+fuzz_target!(|data: &[u8]| {
+    for argc in [None, data.first().map(|&argc| usize::from(argc))] {
+        let Ok(layout) = StackLayoutRef::try_new(data, argc) else {
+            continue;
+        };
+        let _ = layout.argc();
+        let _ = layout.envc();
+        let _ = layout.auxvc();
+        layout.argv_raw_iter().for_each(drop);
+        layout.envv_raw_iter().for_each(drop);
+        layout.auxv_raw_iter().for_each(drop);
+    }
+});
@@ -0,0 +1,79 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/// Target architecture of a stack layout.
+///
+/// The architecture determines the size of a word in the layout, i.e., the
+/// size of `argc`, of the pointers, and of the keys and values of the
+/// auxiliary vector, as well as the alignment of the stack.
+///
+/// The interpretation of [`AuxVar::HwCap`] is architecture-specific as well,
+/// but this crate passes the value through unmodified.
+///
+/// [`AuxVar::HwCap`]: crate::AuxVar::HwCap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arch {
+    /// 32-bit x86 (`i386`).
+    X86,
+    /// 64-bit x86 (`x86_64`).
+    X86_64,
+    /// 32-bit ARM (`arm`).
+    AArch32,
+    /// 64-bit ARM (`aarch64`).
+    AArch64,
+    /// 32-bit RISC-V (`rv32`).
+    RiscV32,
+    /// 64-bit RISC-V (`rv64`).
+    RiscV64,
+    /// 32-bit MIPS (`o32` ABI).
+    Mips32,
+    /// 64-bit MIPS (`n64` ABI).
+    Mips64,
+}
+
+impl Arch {
+    /// Returns the size of a word in bytes.
+    #[must_use]
+    pub const fn word_size(self) -> usize {
+        match self {
+            Self::X86 | Self::AArch32 | Self::RiscV32 | Self::Mips32 => 4,
+            Self::X86_64 | Self::AArch64 | Self::RiscV64 | Self::Mips64 => 8,
+        }
+    }
+
+    /// Returns the alignment in bytes of the stack pointer at process entry
+    /// as required by the ABI.
+    #[must_use]
+    pub const fn stack_alignment(self) -> usize {
+        match self {
+            Self::AArch32 | Self::Mips32 => 8,
+            Self::X86
+            | Self::X86_64
+            | Self::AArch64
+            | Self::RiscV32
+            | Self::RiscV64
+            | Self::Mips64 => 16,
+        }
+    }
+}
@@ -45,6 +45,21 @@ bitflags::bitflags! {
     }
 }
 
+/// Alias for [`AuxVarFlags`], named after the `AT_FLAGS` entry that carries
+/// them.
+pub type AtFlags = AuxVarFlags;
+
+impl Display for AuxVarFlags {
+    /// Formats the names of the set flags separated by `|`, for example
+    /// `PRESERVE_ARGV0`. Unknown bits are formatted as hexadecimal number.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("NOT_PRESERVE_ARGV0");
+        }
+        bitflags::parser::to_writer(self, f)
+    }
+}
+
 /// Possible string payload variants of an [`AuxVar`].
 ///
 /// Due to the diverse variants, is not guaranteed that
@@ -55,7 +70,7 @@ bitflags::bitflags! {
 /// necessary. Interim NUL bytes are prohibited.
 ///
 /// This type can be easily construct using `::from()` respectively `.into()`.
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub enum AuxVarString<'a> {
     #[cfg(feature = "alloc")]
     String(String),
@@ -99,6 +114,18 @@ impl<'a> AuxVarString<'a> {
         }
     }
 
+    /// Like [`Self::upgrade_to_owned`] but also detaches the value from the
+    /// lifetime of the borrowed data.
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> AuxVarString<'static> {
+        match self {
+            AuxVarString::String(str) => AuxVarString::String(str),
+            AuxVarString::CString(cstr) => AuxVarString::CString(cstr),
+            AuxVarString::Str(str) => AuxVarString::String(str.to_owned()),
+            AuxVarString::CStr(cstr) => AuxVarString::CString(cstr.to_owned()),
+        }
+    }
+
     /// Transforms the inner value into a owned Rust [`String`].
     #[cfg(feature = "alloc")]
     pub fn into_string(self) -> String {
@@ -162,7 +189,7 @@ impl<'a> From<CString> for AuxVarString<'a> {
 /// * <https://elixir.bootlin.com/linux/latest/source/fs/binfmt_elf.c#L259>
 /// * <https://man7.org/linux/man-pages/man3/getauxval.3.html>
 /// * <https://lwn.net/Articles/631631/>
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub enum AuxVar<'a> {
     /// Entry with payload for type [`AuxVarType::Null`].
     Null,
@@ -260,6 +287,7 @@ impl<'a> AuxVar<'a> {
     /// # Safety
     /// This function creates undefined behavior or might even crash if the
     /// value is an invalid pointer or a pointer pointing to invalid memory.
+    #[must_use]
     pub unsafe fn from_raw(serialized: &AuxVarRaw, buffer: &'a [u8]) -> Self {
         let key = serialized.key().unwrap();
 
@@ -276,7 +304,7 @@ impl<'a> AuxVar<'a> {
             AuxVarType::Random => {
                 let begin_index = serialized.value() - buffer.as_ptr() as usize;
                 let end_index = begin_index + 16 /* 16 bytes of randomness */;
-                assert!(end_index < buffer.len());
+                assert!(end_index <= buffer.len());
 
                 let mut bytes = [0; 16];
                 bytes.copy_from_slice(&buffer[begin_index..end_index]);
@@ -320,6 +348,49 @@ impl<'a> AuxVar<'a> {
         }
     }
 
+    /// Detaches the value from the lifetime of borrowed data by upgrading
+    /// string payloads to owned variants. Other values are returned as is.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn into_owned(self) -> AuxVar<'static> {
+        match self {
+            AuxVar::Null => AuxVar::Null,
+            AuxVar::Ignore => AuxVar::Ignore,
+            AuxVar::ExecFd(val) => AuxVar::ExecFd(val),
+            AuxVar::Phdr(val) => AuxVar::Phdr(val),
+            AuxVar::Phent(val) => AuxVar::Phent(val),
+            AuxVar::Phnum(val) => AuxVar::Phnum(val),
+            AuxVar::Pagesz(val) => AuxVar::Pagesz(val),
+            AuxVar::Base(val) => AuxVar::Base(val),
+            AuxVar::Flags(val) => AuxVar::Flags(val),
+            AuxVar::Entry(val) => AuxVar::Entry(val),
+            AuxVar::NotElf(val) => AuxVar::NotElf(val),
+            AuxVar::Uid(val) => AuxVar::Uid(val),
+            AuxVar::EUid(val) => AuxVar::EUid(val),
+            AuxVar::Gid(val) => AuxVar::Gid(val),
+            AuxVar::EGid(val) => AuxVar::EGid(val),
+            AuxVar::Platform(val) => AuxVar::Platform(val.into_owned()),
+            AuxVar::HwCap(val) => AuxVar::HwCap(val),
+            AuxVar::Clktck(val) => AuxVar::Clktck(val),
+            AuxVar::Secure(val) => AuxVar::Secure(val),
+            AuxVar::BasePlatform(val) => AuxVar::BasePlatform(val.into_owned()),
+            AuxVar::Random(val) => AuxVar::Random(val),
+            AuxVar::HwCap2(val) => AuxVar::HwCap2(val),
+            AuxVar::ExecFn(val) => AuxVar::ExecFn(val.into_owned()),
+            AuxVar::Sysinfo(val) => AuxVar::Sysinfo(val),
+            AuxVar::SysinfoEhdr(val) => AuxVar::SysinfoEhdr(val),
+            AuxVar::L1iCacheSize(val) => AuxVar::L1iCacheSize(val),
+            AuxVar::L1iCacheGeometry(val) => AuxVar::L1iCacheGeometry(val),
+            AuxVar::L1dCacheSize(val) => AuxVar::L1dCacheSize(val),
+            AuxVar::L1dCacheGeometry(val) => AuxVar::L1dCacheGeometry(val),
+            AuxVar::L2CacheSize(val) => AuxVar::L2CacheSize(val),
+            AuxVar::L2CacheGeometry(val) => AuxVar::L2CacheGeometry(val),
+            AuxVar::L3CacheSize(val) => AuxVar::L3CacheSize(val),
+            AuxVar::L3CacheGeometry(val) => AuxVar::L3CacheGeometry(val),
+            AuxVar::MinSigStkSz(val) => AuxVar::MinSigStkSz(val),
+        }
+    }
+
     /// Returns the [`AuxVarType`] this aux var corresponds to.
     #[must_use]
     pub const fn key(&self) -> AuxVarType {
@@ -520,6 +591,33 @@ impl<'a> AuxVar<'a> {
     }
 }
 
+impl AuxVar<'static> {
+    /// Creates an [`AuxVar::Platform`] entry.
+    #[must_use]
+    pub const fn platform(str: &'static str) -> Self {
+        Self::Platform(AuxVarString::Str(str))
+    }
+
+    /// Like [`Self::platform`] but takes ownership of the string.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub const fn platform_owned(str: String) -> Self {
+        Self::Platform(AuxVarString::String(str))
+    }
+
+    /// Creates an [`AuxVar::BasePlatform`] entry.
+    #[must_use]
+    pub const fn base_platform(str: &'static str) -> Self {
+        Self::BasePlatform(AuxVarString::Str(str))
+    }
+
+    /// Creates an [`AuxVar::ExecFn`] entry.
+    #[must_use]
+    pub const fn execfn(str: &'static CStr) -> Self {
+        Self::ExecFn(AuxVarString::CStr(str))
+    }
+}
+
 impl<'a> PartialOrd for AuxVar<'a> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -536,6 +634,7 @@ impl<'a> Ord for AuxVar<'a> {
 mod tests {
     use super::*;
     use std::collections::BTreeSet;
+    use std::string::ToString;
 
     /// Tests that the ATNull entry always comes last in an ordered collection. This enables
     /// us to easily write all AT-VARs at once but keep the terminating null entry at the end.
@@ -549,4 +648,55 @@ mod tests {
         set.insert(AuxVar::ExecFn(c"./executable".into()));
         assert_eq!(set.iter().last().unwrap().key(), AuxVarType::Null);
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_aux_var_factories() {
+        use std::string::String;
+
+        assert_eq!(
+            AuxVar::platform("x86_64"),
+            AuxVar::Platform("x86_64".into())
+        );
+        assert_eq!(
+            AuxVar::platform_owned(String::from("x86_64")),
+            AuxVar::Platform(String::from("x86_64").into())
+        );
+        assert_eq!(
+            AuxVar::base_platform("x86_64"),
+            AuxVar::BasePlatform("x86_64".into())
+        );
+        assert_eq!(AuxVar::execfn(c"./prog"), AuxVar::ExecFn(c"./prog".into()));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_aux_var_hash() {
+        use std::collections::HashSet;
+        use std::string::String;
+
+        let set = HashSet::from([
+            AuxVar::Pagesz(4096),
+            AuxVar::Pagesz(4096),
+            AuxVar::Pagesz(65536),
+            AuxVar::ExecFn(String::from("./prog").into()),
+            AuxVar::ExecFn(String::from("./prog").into()),
+            AuxVar::Random([1; 16]),
+            AuxVar::Random([1; 16]),
+            AuxVar::Random([2; 16]),
+        ]);
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(&AuxVar::ExecFn(String::from("./prog").into())));
+        assert!(!set.contains(&AuxVar::Platform(String::from("./prog").into())));
+    }
+
+    #[test]
+    fn test_aux_var_flags_display() {
+        assert_eq!(AtFlags::empty().to_string(), "NOT_PRESERVE_ARGV0");
+        assert_eq!(AtFlags::PRESERVE_ARGV0.to_string(), "PRESERVE_ARGV0");
+        assert_eq!(
+            AtFlags::from_bits_retain(0b101).to_string(),
+            "PRESERVE_ARGV0 | 0x4"
+        );
+    }
 }
@@ -9,9 +9,11 @@ type AuxVarTypeRaw = usize;
 /// the `value` might be a boolean, an integer, or a pointer into the
 /// _auxv data area_.
 ///
+/// Entries are ordered by their raw `key` first and their `value` second.
+///
 /// [`AuxVar`]: crate::AuxVar
 #[repr(C, packed)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct AuxVarRaw {
     /// Encoded variant of [`AuxVarType`].
     key: AuxVarTypeRaw,
@@ -32,11 +34,72 @@ impl AuxVarRaw {
         self.key.try_into()
     }
 
+    /// Returns the raw type, which may be unknown to [`AuxVarType`].
+    #[must_use]
+    pub const fn key_raw(&self) -> usize {
+        self.key
+    }
+
     /// Returns the raw value.
     #[must_use]
     pub const fn value(&self) -> usize {
         self.value
     }
+
+    /// Returns whether this is the terminating [`AuxVarType::Null`] entry with
+    /// a value of zero.
+    #[must_use]
+    pub const fn is_null(&self) -> bool {
+        self.key == AuxVarType::Null.val() && self.value == 0
+    }
+
+    /// Returns the in-memory representation of the entry as used in the
+    /// Linux ABI, e.g., in `/proc/self/auxv`.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; size_of::<Self>()] {
+        // SAFETY: The type is `repr(C, packed)` and only consists of
+        // integers, so there is no padding and any byte is initialized.
+        unsafe { &*(self as *const Self).cast() }
+    }
+
+    /// Reinterprets the bytes as entry without copying.
+    ///
+    /// Returns `None` if the length of `bytes` doesn't match the size of the
+    /// type.
+    #[must_use]
+    pub const fn from_bytes(bytes: &[u8]) -> Option<&Self> {
+        if bytes.len() != size_of::<Self>() {
+            return None;
+        }
+        // SAFETY: The type is `repr(C, packed)` (alignment of one) and any bit
+        // pattern is valid.
+        Some(unsafe { &*bytes.as_ptr().cast() })
+    }
+
+    /// Reinterprets the bytes as slice of entries without copying. This
+    /// enables zero-copy parsing of `/proc/self/auxv`.
+    ///
+    /// Returns `None` if the length of `bytes` is not a multiple of the size
+    /// of the type.
+    #[must_use]
+    pub const fn slice_from_bytes(bytes: &[u8]) -> Option<&[Self]> {
+        if bytes.len() % size_of::<Self>() != 0 {
+            return None;
+        }
+        let len = bytes.len() / size_of::<Self>();
+        // SAFETY: The type is `repr(C, packed)` (alignment of one) and any bit
+        // pattern is valid.
+        Some(unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast(), len) })
+    }
+
+    /// Returns the in-memory representation of the entries as used in the
+    /// Linux ABI, e.g., in `/proc/self/auxv`.
+    #[must_use]
+    pub const fn slice_as_bytes(entries: &[Self]) -> &[u8] {
+        // SAFETY: The type is `repr(C, packed)` and only consists of
+        // integers, so there is no padding and any byte is initialized.
+        unsafe { core::slice::from_raw_parts(entries.as_ptr().cast(), size_of_val(entries)) }
+    }
 }
 
 impl Debug for AuxVarRaw {
@@ -64,4 +127,50 @@ mod tests {
         // Generic, on all platforms:
         assert_eq!(size_of::<AuxVarRaw>(), 2 * size_of::<usize>());
     }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let entry = AuxVarRaw::new(AuxVarType::Pagesz, 4096);
+        let bytes = entry.as_bytes();
+        assert_eq!(bytes[..size_of::<usize>()], 6_usize.to_ne_bytes());
+        assert_eq!(bytes[size_of::<usize>()..], 4096_usize.to_ne_bytes());
+        assert_eq!(AuxVarRaw::from_bytes(bytes), Some(&entry));
+        assert_eq!(AuxVarRaw::from_bytes(&bytes[1..]), None);
+
+        let entries = [entry, AuxVarRaw::new(AuxVarType::Null, 0)];
+        let bytes = AuxVarRaw::slice_as_bytes(&entries);
+        assert_eq!(bytes.len(), 2 * size_of::<AuxVarRaw>());
+        assert_eq!(AuxVarRaw::slice_from_bytes(bytes), Some(&entries[..]));
+        // also works for unaligned bytes
+        let mut unaligned = std::vec![0_u8];
+        unaligned.extend_from_slice(bytes);
+        assert_eq!(
+            AuxVarRaw::slice_from_bytes(&unaligned[1..]),
+            Some(&entries[..])
+        );
+        assert_eq!(AuxVarRaw::slice_from_bytes(&bytes[1..]), None);
+    }
+
+    #[test]
+    fn test_ord() {
+        use std::collections::BTreeSet;
+
+        let set = BTreeSet::from([
+            AuxVarRaw::new(AuxVarType::Uid, 1000),
+            AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+            AuxVarRaw::new(AuxVarType::Null, 0),
+            AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+            AuxVarRaw::new(AuxVarType::Pagesz, 65536),
+        ]);
+        assert!(set.iter().eq(&[
+            AuxVarRaw::new(AuxVarType::Null, 0),
+            AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+            AuxVarRaw::new(AuxVarType::Pagesz, 65536),
+            AuxVarRaw::new(AuxVarType::Uid, 1000),
+        ]));
+
+        assert!(AuxVarRaw::new(AuxVarType::Null, 0).is_null());
+        assert!(!AuxVarRaw::new(AuxVarType::Null, 1).is_null());
+        assert!(!AuxVarRaw::new(AuxVarType::Pagesz, 0).is_null());
+    }
 }
@@ -35,11 +35,16 @@ pub struct ParseAuxVarTypeError(usize);
 /// - `>=32` are for `x86_64`.
 /// - `>=40` are for power PC.
 ///
+/// MIPS has no architecture-specific types. It only emits
+/// [`AuxVarType::SysinfoEhdr`] in addition to the architecture-independent
+/// ones.
+///
 /// ## More Info
 /// * <https://elixir.bootlin.com/linux/latest/source/include/uapi/linux/auxvec.h>
+/// * <https://elixir.bootlin.com/linux/latest/source/arch/mips/include/uapi/asm/auxvec.h>
 ///
 /// [`AuxVar`]: crate::AuxVar
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(usize)]
 pub enum AuxVarType {
     // ### architecture neutral
@@ -172,6 +177,58 @@ impl AuxVarType {
         self as _
     }
 
+    /// Returns the name of the constant in the C headers of Linux, e.g.,
+    /// `"AT_PAGESZ"`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Null => "AT_NULL",
+            Self::Ignore => "AT_IGNORE",
+            Self::ExecFd => "AT_EXECFD",
+            Self::Phdr => "AT_PHDR",
+            Self::Phent => "AT_PHENT",
+            Self::Phnum => "AT_PHNUM",
+            Self::Pagesz => "AT_PAGESZ",
+            Self::Base => "AT_BASE",
+            Self::Flags => "AT_FLAGS",
+            Self::Entry => "AT_ENTRY",
+            Self::NotElf => "AT_NOTELF",
+            Self::Uid => "AT_UID",
+            Self::EUid => "AT_EUID",
+            Self::Gid => "AT_GID",
+            Self::EGid => "AT_EGID",
+            Self::Platform => "AT_PLATFORM",
+            Self::HwCap => "AT_HWCAP",
+            Self::Clktck => "AT_CLKTCK",
+            Self::Secure => "AT_SECURE",
+            Self::BasePlatform => "AT_BASE_PLATFORM",
+            Self::Random => "AT_RANDOM",
+            Self::HwCap2 => "AT_HWCAP2",
+            Self::ExecFn => "AT_EXECFN",
+            Self::Sysinfo => "AT_SYSINFO",
+            Self::SysinfoEhdr => "AT_SYSINFO_EHDR",
+            Self::L1iCacheSize => "AT_L1I_CACHESIZE",
+            Self::L1iCacheGeometry => "AT_L1I_CACHEGEOMETRY",
+            Self::L1dCacheSize => "AT_L1D_CACHESIZE",
+            Self::L1dCacheGeometry => "AT_L1D_CACHEGEOMETRY",
+            Self::L2CacheSize => "AT_L2_CACHESIZE",
+            Self::L2CacheGeometry => "AT_L2_CACHEGEOMETRY",
+            Self::L3CacheSize => "AT_L3_CACHESIZE",
+            Self::L3CacheGeometry => "AT_L3_CACHEGEOMETRY",
+            Self::MinSigStkSz => "AT_MINSIGSTKSZ",
+        }
+    }
+
+    /// Returns the type with the given name of the constant in the C headers
+    /// of Linux, e.g., `"AT_PAGESZ"`. See [`Self::name`].
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::variants()
+            .iter()
+            .copied()
+            .find(|typ| typ.name() == name)
+    }
+
     /// If this is true, the value of the key should be interpreted as pointer
     /// into the aux vector data area. Otherwise, the value of the key is an
     /// immediate value/integer.
@@ -221,6 +278,26 @@ impl AuxVarType {
         }
     }
 
+    /// Returns whether the dynamic linker of a dynamically linked ELF
+    /// typically requires an entry of this type: [`Self::Phdr`],
+    /// [`Self::Phent`], [`Self::Phnum`], [`Self::Base`], [`Self::Entry`], and
+    /// [`Self::Pagesz`].
+    #[must_use]
+    pub const fn is_mandatory_for_dynamic_elf(self) -> bool {
+        matches!(
+            self,
+            Self::Phdr | Self::Phent | Self::Phnum | Self::Base | Self::Entry | Self::Pagesz
+        )
+    }
+
+    /// Returns whether the libc of a statically linked ELF typically requires
+    /// an entry of this type: [`Self::Pagesz`], [`Self::Uid`], and
+    /// [`Self::Gid`].
+    #[must_use]
+    pub const fn is_mandatory_for_static_elf(self) -> bool {
+        matches!(self, Self::Pagesz | Self::Uid | Self::Gid)
+    }
+
     /// The payload of entries where this returns true represents a
     /// null-terminated C-string.
     #[must_use]
@@ -288,6 +365,31 @@ mod tests {
         assert_eq!(AuxVarType::variants(), variants.as_slice());
     }
 
+    #[test]
+    fn test_is_mandatory() {
+        let dynamic = AuxVarType::variants()
+            .iter()
+            .filter(|typ| typ.is_mandatory_for_dynamic_elf())
+            .count();
+        assert_eq!(dynamic, 6);
+        assert!(AuxVarType::Base.is_mandatory_for_dynamic_elf());
+        assert!(!AuxVarType::Base.is_mandatory_for_static_elf());
+        assert!(AuxVarType::Pagesz.is_mandatory_for_static_elf());
+        assert!(!AuxVarType::Random.is_mandatory_for_dynamic_elf());
+        assert!(!AuxVarType::Random.is_mandatory_for_static_elf());
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(AuxVarType::Pagesz.name(), "AT_PAGESZ");
+        assert_eq!(AuxVarType::BasePlatform.name(), "AT_BASE_PLATFORM");
+        assert_eq!(AuxVarType::from_name("AT_EXECFN"), Some(AuxVarType::ExecFn));
+        assert_eq!(AuxVarType::from_name("AT_FOO"), None);
+        for &typ in AuxVarType::variants() {
+            assert_eq!(AuxVarType::from_name(typ.name()), Some(typ));
+        }
+    }
+
     /// Tests that the ATNull entry always comes last in an ordered collection.
     /// This enables us to easily write all AT-VARs at once but keep the
     /// terminating null entry at the end.
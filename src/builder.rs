@@ -21,54 +21,496 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
+use crate::serializer::StackLayoutSerializer;
 use crate::util::get_null_index;
-use crate::{AuxVar, AuxVarRaw, AuxVarType};
+use crate::{
+    Arch, AtFlags, AuxVar, AuxVarRaw, AuxVarType, KERNEL_AUXV_ORDER, MAX_ARG_STRINGS,
+    MAX_ARG_STRLEN,
+};
 use aligned_vec::{ABox, AVec};
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::ffi::CStr;
+use core::fmt::{Debug, Display, Formatter};
+#[cfg(all(feature = "std", unix))]
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+#[cfg(feature = "std")]
+use {
+    alloc::borrow::ToOwned,
+    alloc::ffi::CString,
+    std::io::{self, Write},
+    std::process::Child,
+};
 
 /// Builder to create a stack layout as described by the [`StackLayoutRef`]
 /// type.
 ///
+/// Cloning the builder is shallow for borrowed string payloads of the
+/// [`AuxVar`]s. Use [`Self::into_owned`] to get a builder that is independent
+/// of the borrowed data.
+///
 /// [`StackLayoutRef`]: crate::StackLayoutRef
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Default, PartialEq, Eq)]
 pub struct StackLayoutBuilder<'a> {
-    argv: Vec<String>,
-    envv: Vec<String>,
+    // NUL-terminated strings, not necessarily UTF-8
+    argv: Vec<Vec<u8>>,
+    // NUL-terminated strings, not necessarily UTF-8
+    envv: Vec<Vec<u8>>,
     auxv: Vec<AuxVar<'a>>,
+    // Written after `auxv`, the type may be unknown
+    auxv_raw: Vec<AuxVarRaw>,
+    enforce_linux_limits: bool,
+    envv_key_policy: EnvKeyPolicy,
+    // `None` means the architecture of the host
+    arch: Option<Arch>,
+    // `None` means the number of arguments
+    argc_override: Option<usize>,
+    sort_auxv: bool,
+    // Identical strings share their data when enabled
+    string_pool: bool,
+    // `None` means `2 * align_of::<usize>()`
+    stack_alignment: Option<usize>,
+    // Duplicate arguments are dropped when enabled
+    argv_dedup: bool,
+    // `None` means no padding before the data areas
+    data_area_alignment: Option<usize>,
+    // Implausible entries added so far, see `take_warnings()`
+    #[cfg(feature = "sanity_warnings")]
+    warnings: Vec<PlausibilityWarning>,
+}
+
+/// Errors that are reported when [`StackLayoutBuilder::enforce_linux_limits`]
+/// is enabled and a limit of Linux is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum LimitError {
+    /// The string, including the terminating NUL byte, is longer than
+    /// [`MAX_ARG_STRLEN`].
+    #[error("string exceeds MAX_ARG_STRLEN ({MAX_ARG_STRLEN} bytes)")]
+    StringTooLong,
+    /// There are more than [`MAX_ARG_STRINGS`] strings.
+    #[error("number of strings exceeds MAX_ARG_STRINGS ({MAX_ARG_STRINGS})")]
+    TooManyStrings,
+}
+
+/// Errors that are reported by [`StackLayoutBuilder::try_add_envv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum EnvError {
+    /// A limit of Linux is exceeded.
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    /// The key violates the [`EnvKeyPolicy`] of the builder.
+    #[error("environment variable key is not a valid POSIX name")]
+    InvalidKey,
+}
+
+/// Errors that are reported by [`StackLayoutBuilder::add_argv_os`] and
+/// [`StackLayoutBuilder::add_envv_os`].
+#[cfg(all(feature = "std", unix))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum OsStrError {
+    /// The string contains interim NUL bytes.
+    #[error("string contains interim NUL bytes")]
+    InvalidEncoding,
+    /// A limit of Linux is exceeded.
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    /// The key violates the [`EnvKeyPolicy`] of the builder.
+    #[error("environment variable key is not a valid POSIX name")]
+    InvalidKey,
+}
+
+#[cfg(all(feature = "std", unix))]
+impl From<EnvError> for OsStrError {
+    fn from(value: EnvError) -> Self {
+        match value {
+            EnvError::Limit(e) => Self::Limit(e),
+            EnvError::InvalidKey => Self::InvalidKey,
+        }
+    }
+}
+
+/// Errors that are reported by [`StackLayoutBuilder::try_add_auxv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AddAuxvError {
+    /// [`AuxVar::Null`] was added explicitly. The terminating entry is always
+    /// added automatically.
+    #[error("AT_NULL must not be added explicitly")]
+    ExplicitNull,
+}
+
+/// Potential issues of a [`StackLayoutBuilder`] as reported by
+/// [`StackLayoutBuilder::check_auxv_consistency`].
+///
+/// None of them prevents building the layout, but they often indicate a bug.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyWarning {
+    /// There are multiple [`AuxVar`]s of the given type.
+    DuplicateType(AuxVarType),
+    /// [`AuxVar::ExecFn`] and [`AuxVar::Platform`] have the same value.
+    ExecFnEqualsPlatform,
+    /// `argc` was overridden by [`StackLayoutBuilder::override_argc`] with a
+    /// value that doesn't match the number of arguments.
+    ArgcMismatch,
+}
+
+/// Implausible values of [`AuxVar`]s as reported by
+/// [`StackLayoutBuilder::check_auxv_plausibility`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlausibilityWarning {
+    /// The value of [`AuxVar::Pagesz`] is not a power of two.
+    PageSizeNotPowerOfTwo(usize),
+    /// The value of [`AuxVar::Phent`] is not the size of an ELF program
+    /// header, i.e., 32, 40, or 56.
+    InvalidProgramHeaderSize(usize),
+    /// The value of [`AuxVar::Phnum`] exceeds 65535.
+    TooManyProgramHeaders(usize),
+    /// The user or group ID of the given type doesn't fit into a `u32`.
+    IdExceedsU32(AuxVarType, usize),
+}
+
+impl Display for PlausibilityWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::PageSizeNotPowerOfTwo(size) => {
+                write!(f, "AT_PAGESZ {size} is not a power of two")
+            }
+            Self::InvalidProgramHeaderSize(size) => {
+                write!(f, "AT_PHENT {size} is not a valid ELF program header size")
+            }
+            Self::TooManyProgramHeaders(num) => write!(f, "AT_PHNUM {num} exceeds 65535"),
+            Self::IdExceedsU32(key, id) => write!(f, "{key:?} {id} doesn't fit into a u32"),
+        }
+    }
+}
+
+/// Placement of the strings in the data areas of a built layout as reported by
+/// [`StackLayoutBuilder::compute_data_map`].
+///
+/// Each string is described by its offset into the buffer and its length in
+/// bytes, including the NUL byte.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataMap {
+    /// The strings of the arguments, in the order of `argv`.
+    pub argv_strings: Vec<(usize, usize)>,
+    /// The strings of the environment variables, in the order of `envv`.
+    pub envv_strings: Vec<(usize, usize)>,
+    /// The data of the [`AuxVar`]s that reference the data area, in the order
+    /// of `auxv`. [`AuxVar::Random`] has no NUL byte.
+    pub auxv_strings: Vec<(AuxVarType, usize, usize)>,
+}
+
+/// Policy for the keys of environment variables added to a
+/// [`StackLayoutBuilder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EnvKeyPolicy {
+    /// Every non-empty key without a `=` is accepted.
+    #[default]
+    Allow,
+    /// Only keys consisting of ASCII alphanumeric characters and underscores
+    /// that don't start with a digit are accepted, as described by POSIX.
+    PosixStrict,
+}
+
+impl EnvKeyPolicy {
+    /// Returns whether `key` is accepted by the policy.
+    #[must_use]
+    pub fn accepts(self, key: &str) -> bool {
+        self.accepts_bytes(key.as_bytes())
+    }
+
+    /// Like [`Self::accepts`] but for keys that are not necessarily UTF-8.
+    fn accepts_bytes(self, key: &[u8]) -> bool {
+        match self {
+            Self::Allow => !key.is_empty(),
+            Self::PosixStrict => {
+                key.iter().all(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                    && key.first().is_some_and(|b| !b.is_ascii_digit())
+            }
+        }
+    }
 }
 
 impl<'a> StackLayoutBuilder<'a> {
-    /// Creates a mew bioöder-
+    /// Creates a new, empty builder.
+    ///
+    /// This doesn't allocate and can be used in `const` contexts.
     #[must_use]
     pub const fn new() -> Self {
         Self {
-            argv: vec![],
-            envv: vec![],
-            auxv: vec![],
+            argv: Vec::new(),
+            envv: Vec::new(),
+            auxv: Vec::new(),
+            auxv_raw: Vec::new(),
+            enforce_linux_limits: false,
+            envv_key_policy: EnvKeyPolicy::Allow,
+            arch: None,
+            argc_override: None,
+            sort_auxv: false,
+            string_pool: false,
+            stack_alignment: None,
+            argv_dedup: false,
+            data_area_alignment: None,
+            #[cfg(feature = "sanity_warnings")]
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Creates a new builder for the given target [`Arch`].
+    ///
+    /// This is [`Self::new`] followed by [`Self::with_arch`] and
+    /// [`Self::with_stack_alignment`] with the stack alignment of the
+    /// architecture's ABI.
+    #[must_use]
+    pub const fn for_arch(arch: Arch) -> Self {
+        Self::new()
+            .with_arch(arch)
+            .with_stack_alignment(arch.stack_alignment())
+    }
+
+    /// Detaches the builder from the lifetime of borrowed data by upgrading
+    /// all [`AuxVar`]s with string payloads to owned variants.
+    ///
+    /// See [`AuxVar::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> StackLayoutBuilder<'static> {
+        StackLayoutBuilder {
+            argv: self.argv,
+            envv: self.envv,
+            auxv: self.auxv.into_iter().map(AuxVar::into_owned).collect(),
+            auxv_raw: self.auxv_raw,
+            enforce_linux_limits: self.enforce_linux_limits,
+            envv_key_policy: self.envv_key_policy,
+            arch: self.arch,
+            argc_override: self.argc_override,
+            sort_auxv: self.sort_auxv,
+            string_pool: self.string_pool,
+            stack_alignment: self.stack_alignment,
+            argv_dedup: self.argv_dedup,
+            data_area_alignment: self.data_area_alignment,
+            #[cfg(feature = "sanity_warnings")]
+            warnings: self.warnings,
+        }
+    }
+
+    /// Enables or disables the enforcement of the limits Linux puts on
+    /// arguments and environment variables, i.e., [`MAX_ARG_STRLEN`] and
+    /// [`MAX_ARG_STRINGS`].
+    ///
+    /// When enabled, [`Self::try_add_argv`] and [`Self::try_add_envv`] report
+    /// a [`LimitError`] and [`Self::add_argv`] and [`Self::add_envv`] panic if
+    /// a limit is exceeded. This is disabled by default.
+    pub const fn enforce_linux_limits(&mut self, enable: bool) {
+        self.enforce_linux_limits = enable;
+    }
+
+    /// Sets the [`EnvKeyPolicy`] that keys of environment variables must
+    /// follow. This is [`EnvKeyPolicy::Allow`] by default.
+    ///
+    /// The policy only applies to environment variables added afterwards.
+    pub const fn set_envv_key_policy(&mut self, policy: EnvKeyPolicy) {
+        self.envv_key_policy = policy;
+    }
+
+    /// Enables or disables the silent removal of duplicate arguments. This is
+    /// disabled by default.
+    ///
+    /// When enabled, [`Self::add_argv`] and its variants drop arguments that
+    /// are already present. The option only applies to arguments added
+    /// afterwards. See [`Self::check_argv_duplicates`] to find duplicates
+    /// instead.
+    pub const fn set_argv_dedup(&mut self, enable: bool) {
+        self.argv_dedup = enable;
+    }
+
+    /// Checks the string (including the NUL byte) that should be added as
+    /// entry number `count + 1` against the limits of Linux, if enabled.
+    const fn check_linux_limits(&self, str: &[u8], count: usize) -> Result<(), LimitError> {
+        if !self.enforce_linux_limits {
+            return Ok(());
+        }
+        if str.len() > MAX_ARG_STRLEN {
+            return Err(LimitError::StringTooLong);
+        }
+        if count >= MAX_ARG_STRINGS {
+            return Err(LimitError::TooManyStrings);
+        }
+        Ok(())
+    }
+
+    /// Appends the terminating NUL byte to `str`, if not present yet.
+    ///
+    /// Reports the position of the first interim NUL byte as error.
+    pub(crate) fn try_nul_terminate(mut str: Vec<u8>) -> Result<Vec<u8>, usize> {
+        match get_null_index(&str) {
+            Some(pos) if pos != str.len() - 1 => return Err(pos),
+            Some(_) => {}
+            None => str.push(0),
+        }
+        Ok(str)
+    }
+
+    /// Like [`Self::try_nul_terminate`] but for UTF-8 strings.
+    ///
+    /// # Panics
+    /// Panics if `str` contains interim NUL bytes. The message is prefixed
+    /// with `context` and contains the first 64 characters of `str`.
+    fn nul_terminate(str: String, context: &str) -> Vec<u8> {
+        if let Some(pos) = str.find('\0').filter(|&pos| pos != str.len() - 1) {
+            let str = str.chars().take(64).collect::<String>();
+            panic!("{context}: string {str:?} contains an interior NUL byte at position {pos}");
+        }
+        Self::try_nul_terminate(str.into_bytes()).expect("should have no interim NUL bytes")
+    }
+
+    /// Overrides the `argc` value written into the layout, which is the
+    /// number of arguments by default.
+    ///
+    /// This is only needed for exotic loaders. The resulting layout is
+    /// inconsistent if `argc` doesn't match the number of arguments, which
+    /// breaks parsers that locate `envv` via `argc`, such as the
+    /// [`StackLayoutRef`].
+    ///
+    /// [`StackLayoutRef`]: crate::StackLayoutRef
+    pub const fn override_argc(&mut self, argc: usize) {
+        self.argc_override = Some(argc);
+    }
+
+    /// Sets the program name, i.e., `argv[0]`.
+    ///
+    /// By convention, the first argument is the name or path of the program.
+    /// This replaces an existing first argument or inserts it if there are no
+    /// arguments yet.
+    ///
+    /// # Panics
+    /// Panics if `name` is empty or contains interim NUL bytes.
+    pub fn set_program_name(&mut self, name: impl Into<String>) {
+        let name = Self::nul_terminate(name.into(), "set_program_name");
+        assert!(name.len() > 1, "the program name must not be empty");
+
+        match self.argv.first_mut() {
+            Some(first) => *first = name,
+            None => self.argv.push(name),
         }
     }
 
+    /// Returns the program name, i.e., `argv[0]` without the NUL byte, if
+    /// there is at least one argument.
+    ///
+    /// Returns `None` as well if the program name is not valid UTF-8, which
+    /// is only possible when it was added by `add_argv_os()`.
+    #[must_use]
+    pub fn program_name(&self) -> Option<&str> {
+        self.argv
+            .first()
+            .and_then(|arg| core::str::from_utf8(&arg[..arg.len() - 1]).ok())
+    }
+
     /// Adds an argument to the builder.
     ///
     /// Adding a terminating NUL byte is not necessary. Interim NUL bytes are
     /// prohibited.
+    ///
+    /// # Panics
+    /// Panics if [`Self::try_add_argv`] fails.
     pub fn add_argv(&mut self, arg: impl Into<String>) {
-        let mut arg = arg.into();
-        if let Some(pos) = arg.find('\0') {
-            assert_eq!(
-                pos,
-                arg.len() - 1,
-                "strings must not contain interim NUL bytes"
-            );
+        if let Err(e) = self.try_add_argv(arg) {
+            panic!("{e}");
         }
+    }
 
-        if !arg.ends_with('\0') {
-            arg.push('\0');
-        }
+    /// Like [`Self::add_argv`] but reports exceeded limits if
+    /// [`Self::enforce_linux_limits`] is enabled.
+    pub fn try_add_argv(&mut self, arg: impl Into<String>) -> Result<(), LimitError> {
+        self.push_argv(Self::nul_terminate(arg.into(), "add_argv"))
+    }
 
+    /// Adds an argument that is not necessarily UTF-8 to the builder.
+    ///
+    /// This is the counterpart of [`Self::try_add_argv`] for the raw bytes of
+    /// an [`OsStr`], such as a path or an argument of the current process.
+    #[cfg(all(feature = "std", unix))]
+    pub fn add_argv_os(&mut self, arg: &OsStr) -> Result<(), OsStrError> {
+        let arg = Self::try_nul_terminate(arg.as_bytes().to_vec())
+            .map_err(|_| OsStrError::InvalidEncoding)?;
+        self.push_argv(arg)?;
+        Ok(())
+    }
+
+    /// Adds the NUL-terminated `arg` after checking the limits.
+    pub(crate) fn push_argv(&mut self, arg: Vec<u8>) -> Result<(), LimitError> {
+        if self.argv_dedup && self.argv.contains(&arg) {
+            return Ok(());
+        }
+        self.check_linux_limits(&arg, self.argv.len())?;
         self.argv.push(arg);
+        Ok(())
+    }
+
+    /// Returns all duplicate arguments as `(index_a, index_b, string)`, where
+    /// `index_a` is the index of the first occurrence and `index_b` the index
+    /// of a later occurrence of `string`.
+    ///
+    /// Arguments that are not valid UTF-8 are not reported.
+    #[must_use]
+    pub fn check_argv_duplicates(&self) -> Vec<(usize, usize, &str)> {
+        let mut duplicates = Vec::new();
+        for (index_b, arg) in self.argv.iter().enumerate() {
+            let Some(index_a) = self.argv[..index_b].iter().position(|other| other == arg) else {
+                continue;
+            };
+            if let Ok(str) = core::str::from_utf8(&arg[..arg.len() - 1]) {
+                duplicates.push((index_a, index_b, str));
+            }
+        }
+        duplicates
+    }
+
+    /// Returns the [`PlausibilityWarning`]s of the [`AuxVar`]s added since the
+    /// last call and clears them.
+    ///
+    /// The warnings are reported by [`Self::check_auxv_plausibility`] for each
+    /// added [`AuxVar`]. They don't prevent building the layout.
+    #[cfg(feature = "sanity_warnings")]
+    #[must_use]
+    pub fn take_warnings(&mut self) -> Vec<PlausibilityWarning> {
+        core::mem::take(&mut self.warnings)
+    }
+
+    /// Returns whether the [`AuxVar`]s added so far are sorted by the numeric
+    /// value of their [`AuxVarType`].
+    ///
+    /// This is the same as [`Self::auxv_is_sorted`] and exists for consistency
+    /// with [`Self::is_envv_unique`].
+    #[must_use]
+    pub fn is_auxv_sorted(&self) -> bool {
+        self.auxv_is_sorted()
+    }
+
+    /// Returns whether the keys of the environment variables added so far are
+    /// distinct.
+    ///
+    /// For duplicate keys, it depends on the program which value is used.
+    #[must_use]
+    pub fn is_envv_unique(&self) -> bool {
+        let mut keys = BTreeSet::new();
+        self.envv.iter().all(|env| {
+            let pos = env
+                .iter()
+                .position(|&b| b == b'=')
+                .expect("should have ENV var syntax (`key=value`)");
+            keys.insert(&env[..pos])
+        })
+    }
+
+    /// Adds all arguments to the builder.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Self::add_argv`].
+    pub fn extend_argv<S: Into<String>>(&mut self, args: impl IntoIterator<Item = S>) {
+        for arg in args {
+            self.add_argv(arg);
+        }
     }
 
     /// Adds an environment-variable to the builder.
@@ -78,62 +520,489 @@ impl<'a> StackLayoutBuilder<'a> {
     ///
     /// The value must follow the `key=value` syntax, where `value` may be
     /// empty.
+    ///
+    /// # Panics
+    /// Panics if [`Self::try_add_envv`] fails.
     pub fn add_envv(&mut self, env: impl Into<String>) {
-        let mut env = env.into();
-        if let Some(pos) = env.find('\0') {
-            assert_eq!(
-                pos,
-                env.len() - 1,
-                "strings must not contain interim NUL bytes"
-            );
+        if let Err(e) = self.try_add_envv(env) {
+            panic!("{e}");
+        }
+    }
+
+    /// Adds all environment variables to the builder.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Self::add_envv`].
+    pub fn extend_envv<S: Into<String>>(&mut self, envs: impl IntoIterator<Item = S>) {
+        for env in envs {
+            self.add_envv(env);
         }
+    }
 
-        if !env.ends_with('\0') {
-            env.push('\0');
+    /// Adds all key-value pairs as environment variables to the builder.
+    ///
+    /// This is a convenient alternative to calling [`Self::add_envv`] with
+    /// `format!("{key}={value}")`. The order follows the iteration order of
+    /// `map`, which is unspecified for a `HashMap`.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Self::add_envv`].
+    pub fn add_envv_from_map<K, V>(&mut self, map: impl IntoIterator<Item = (K, V)>)
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (key, value) in map {
+            self.add_envv(format!("{}={}", key.as_ref(), value.as_ref()));
         }
+    }
+
+    /// Like [`Self::add_envv`] but reports exceeded limits if
+    /// [`Self::enforce_linux_limits`] is enabled and keys that violate the
+    /// [`EnvKeyPolicy`] set by [`Self::set_envv_key_policy`].
+    pub fn try_add_envv(&mut self, env: impl Into<String>) -> Result<(), EnvError> {
+        self.push_envv(Self::nul_terminate(env.into(), "add_envv"))
+    }
+
+    /// Adds an environment variable that is not necessarily UTF-8 to the
+    /// builder.
+    ///
+    /// This is the counterpart of [`Self::try_add_envv`] for the raw bytes of
+    /// an [`OsStr`]. The value must follow the `key=value` syntax as well.
+    #[cfg(all(feature = "std", unix))]
+    pub fn add_envv_os(&mut self, env: &OsStr) -> Result<(), OsStrError> {
+        let env = Self::try_nul_terminate(env.as_bytes().to_vec())
+            .map_err(|_| OsStrError::InvalidEncoding)?;
+        self.push_envv(env)?;
+        Ok(())
+    }
 
+    /// Adds the NUL-terminated `env` after checking its syntax, the key
+    /// policy, and the limits.
+    pub(crate) fn push_envv(&mut self, env: Vec<u8>) -> Result<(), EnvError> {
         // Check syntax
         {
-            let (key, _value) = env
-                .split_once('=')
+            let pos = env
+                .iter()
+                .position(|&b| b == b'=')
                 .expect("should have ENV var syntax (`key=value`)");
+            let key = &env[..pos];
             assert!(!key.is_empty());
+            if !self.envv_key_policy.accepts_bytes(key) {
+                return Err(EnvError::InvalidKey);
+            }
         }
+
+        self.check_linux_limits(&env, self.envv.len())?;
         self.envv.push(env);
+        Ok(())
+    }
+
+    /// Returns copies of the arguments and environment variables added so far
+    /// as `(argv, envv)`.
+    ///
+    /// This is useful to launch a process conventionally, e.g., via `execve`,
+    /// with the same arguments and environment variables.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn to_exec_args(&self) -> (Vec<CString>, Vec<CString>) {
+        let to_cstrings = |strs: &[Vec<u8>]| {
+            strs.iter()
+                .map(|str| {
+                    CStr::from_bytes_with_nul(str)
+                        .expect("should be NUL-terminated without interim NUL bytes")
+                        .to_owned()
+                })
+                .collect()
+        };
+        (to_cstrings(&self.argv), to_cstrings(&self.envv))
     }
 
     /// Adds an [`AuxVar`] to the builder.
+    ///
+    /// [`AuxVar::Null`] is silently ignored, as the terminating entry is added
+    /// automatically. Use [`Self::try_add_auxv`] to detect this.
     pub fn add_auxv(&mut self, aux: AuxVar<'a>) {
         // Ignore, we do this automatically in the end.
-        if aux != AuxVar::Null {
-            self.auxv.push(aux);
+        let _ = self.try_add_auxv(aux);
+    }
+
+    /// Adds a raw `auxv` entry to the builder without converting it to an
+    /// [`AuxVar`], e.g., to forward entries of types that are unknown to
+    /// [`AuxVarType`].
+    ///
+    /// The value is written as is, so it must not reference data. Raw entries
+    /// are written after all [`AuxVar`]s in the order they were added.
+    /// Entries of type [`AuxVarType::Null`] are silently ignored, as the
+    /// terminating entry is added automatically.
+    pub fn add_auxv_raw(&mut self, raw: AuxVarRaw) {
+        if raw.key_raw() != AuxVarType::Null.val() {
+            self.auxv_raw.push(raw);
+        }
+    }
+
+    /// Like [`Self::add_auxv`] but reports an explicitly added
+    /// [`AuxVar::Null`] as error.
+    pub fn try_add_auxv(&mut self, aux: AuxVar<'a>) -> Result<(), AddAuxvError> {
+        if aux == AuxVar::Null {
+            return Err(AddAuxvError::ExplicitNull);
+        }
+        #[cfg(feature = "sanity_warnings")]
+        if let Some(warning) = Self::check_auxv_plausibility(&aux) {
+            self.warnings.push(warning);
+        }
+        self.auxv.push(aux);
+        Ok(())
+    }
+
+    /// Checks the value of `aux` for plausibility, e.g., that the page size is
+    /// a power of two.
+    ///
+    /// With the `sanity_warnings` feature, this is checked for each added
+    /// [`AuxVar`] and violations are collected for `take_warnings()`.
+    #[must_use]
+    pub const fn check_auxv_plausibility(aux: &AuxVar<'_>) -> Option<PlausibilityWarning> {
+        match *aux {
+            AuxVar::Pagesz(size) if !size.is_power_of_two() => {
+                Some(PlausibilityWarning::PageSizeNotPowerOfTwo(size))
+            }
+            AuxVar::Phent(size) if !matches!(size, 32 | 40 | 56) => {
+                Some(PlausibilityWarning::InvalidProgramHeaderSize(size))
+            }
+            AuxVar::Phnum(num) if num > 0xffff => {
+                Some(PlausibilityWarning::TooManyProgramHeaders(num))
+            }
+            AuxVar::Uid(id) | AuxVar::EUid(id) | AuxVar::Gid(id) | AuxVar::EGid(id)
+                if id > u32::MAX as usize =>
+            {
+                Some(PlausibilityWarning::IdExceedsU32(aux.key(), id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks the builder for potential issues, such as duplicate [`AuxVar`]
+    /// types.
+    ///
+    /// Callers can inspect the returned [`ConsistencyWarning`]s and decide
+    /// whether to proceed. An empty list means that no issues were found.
+    #[must_use]
+    pub fn check_auxv_consistency(&self) -> Vec<ConsistencyWarning> {
+        let mut warnings = Vec::new();
+
+        for (i, aux) in self.auxv.iter().enumerate() {
+            let is_duplicate = self.auxv[i + 1..]
+                .iter()
+                .any(|other| other.key() == aux.key());
+            let is_reported = self.auxv[..i].iter().any(|other| other.key() == aux.key());
+            if is_duplicate && !is_reported {
+                warnings.push(ConsistencyWarning::DuplicateType(aux.key()));
+            }
+        }
+
+        let find_str = |key: AuxVarType| {
+            self.auxv
+                .iter()
+                .find(|aux| aux.key() == key)
+                .and_then(AuxVar::value_payload_str)
+                .map(|str| &str.as_bytes()[..str.count_bytes()])
+        };
+        if let (Some(execfn), Some(platform)) =
+            (find_str(AuxVarType::ExecFn), find_str(AuxVarType::Platform))
+            && execfn == platform
+        {
+            warnings.push(ConsistencyWarning::ExecFnEqualsPlatform);
+        }
+
+        if self
+            .argc_override
+            .is_some_and(|argc| argc != self.argv.len())
+        {
+            warnings.push(ConsistencyWarning::ArgcMismatch);
+        }
+
+        warnings
+    }
+
+    /// Returns whether both builders describe the same layout content, unlike
+    /// [`PartialEq`], which also compares the order of the [`AuxVar`]s and
+    /// all options.
+    ///
+    /// The arguments and environment variables are compared in order, the
+    /// [`AuxVar`]s independent of their order. String payloads are compared
+    /// by their content, regardless of their representation. The effective
+    /// `argc` must match as well.
+    #[must_use]
+    pub fn semantically_eq(&self, other: &StackLayoutBuilder<'_>) -> bool {
+        fn str_bytes<'s>(aux: &'s AuxVar<'_>) -> Option<&'s [u8]> {
+            aux.value_payload_str()
+                .map(|str| &str.as_bytes()[..str.count_bytes()])
+        }
+        fn payload_eq(a: &AuxVar<'_>, b: &AuxVar<'_>) -> bool {
+            match (str_bytes(a), str_bytes(b)) {
+                (Some(a_str), Some(b_str)) => a.key() == b.key() && a_str == b_str,
+                _ => a == b,
+            }
+        }
+
+        let argc =
+            |builder: &StackLayoutBuilder<'_>| builder.argc_override.unwrap_or(builder.argv.len());
+        if self.argv != other.argv
+            || self.envv != other.envv
+            || self.auxv_raw != other.auxv_raw
+            || argc(self) != argc(other)
+            || self.auxv.len() != other.auxv.len()
+        {
+            return false;
+        }
+
+        // Each entry of `other` can only match a single entry of `self`.
+        let mut unmatched = other.auxv.iter().collect::<Vec<_>>();
+        self.auxv.iter().all(|aux| {
+            unmatched
+                .iter()
+                .position(|other| payload_eq(aux, other))
+                .map(|pos| unmatched.swap_remove(pos))
+                .is_some()
+        })
+    }
+
+    /// Checks that the builder contains all [`AuxVar`]s that a dynamically
+    /// linked ELF typically requires, see
+    /// [`AuxVarType::is_mandatory_for_dynamic_elf`].
+    ///
+    /// # Errors
+    /// Returns the missing types in the order of [`AuxVarType::variants`].
+    pub fn validate_for_dynamic_elf(&self) -> Result<(), Vec<AuxVarType>> {
+        let missing = AuxVarType::variants()
+            .iter()
+            .copied()
+            .filter(|typ| typ.is_mandatory_for_dynamic_elf())
+            .filter(|&typ| !self.auxv.iter().any(|aux| aux.key() == typ))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Sets the [`AuxVar::Flags`] entry to `flags`. An existing entry is
+    /// replaced.
+    #[must_use]
+    pub fn with_at_flags(mut self, flags: AtFlags) -> Self {
+        match self
+            .auxv
+            .iter_mut()
+            .find(|aux| aux.key() == AuxVarType::Flags)
+        {
+            Some(aux) => *aux = AuxVar::Flags(flags),
+            None => self.auxv.push(AuxVar::Flags(flags)),
         }
+        self
+    }
+
+    /// Sets the [`AuxVar::Platform`] entry to the architecture of the host, as
+    /// reported by [`std::env::consts::ARCH`], e.g., `"x86_64"` or
+    /// `"aarch64"`. An existing entry is replaced.
+    ///
+    /// [`AuxVar::BasePlatform`] is left untouched: Linux emits it only on
+    /// PowerPC, where it names the actual CPU, which can't be derived from
+    /// the architecture alone.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn with_platform_autodetect(mut self) -> Self {
+        let platform = AuxVar::Platform(std::env::consts::ARCH.into());
+        match self
+            .auxv
+            .iter_mut()
+            .find(|aux| aux.key() == AuxVarType::Platform)
+        {
+            Some(aux) => *aux = platform,
+            None => self.auxv.push(platform),
+        }
+        self
+    }
+
+    /// Enables or disables sorting the [`AuxVar`]s by the numeric value of
+    /// their [`AuxVarType`] when the layout is built.
+    ///
+    /// When enabled, the output doesn't depend on the order in which the
+    /// entries were added. This is disabled by default.
+    pub const fn set_auxv_sort_before_build(&mut self, enable: bool) {
+        self.sort_auxv = enable;
+    }
+
+    /// Returns whether the [`AuxVar`]s added so far are sorted by the numeric
+    /// value of their [`AuxVarType`].
+    #[must_use]
+    pub fn auxv_is_sorted(&self) -> bool {
+        self.auxv.is_sorted_by_key(|aux| aux.key().val())
+    }
+
+    /// Sorts the [`AuxVar`]s added so far in the order in which Linux emits
+    /// them, as described by [`KERNEL_AUXV_ORDER`].
+    ///
+    /// Entries of types that are not part of that list are moved to the end
+    /// while keeping their relative order.
+    #[must_use]
+    pub fn with_kernel_ordering(mut self) -> Self {
+        self.auxv.sort_by_key(|aux| {
+            KERNEL_AUXV_ORDER
+                .iter()
+                .position(|&key| key == aux.key())
+                .unwrap_or(KERNEL_AUXV_ORDER.len())
+        });
+        self
+    }
+
+    /// Sets the target [`Arch`] of the layout.
+    ///
+    /// By default, the layout is built for the architecture of the host. The
+    /// architecture determines the size of `argc`, of the pointers, and of the
+    /// `auxv` entries. For example, [`Arch::RiscV32`] produces 32-bit words
+    /// even on a 64-bit host.
+    ///
+    /// The stack alignment is not changed. Use [`Self::for_arch`] to also
+    /// apply the stack alignment of the architecture.
+    #[must_use]
+    pub const fn with_arch(mut self, arch: Arch) -> Self {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// Enables interning of strings: identical arguments share a single copy in
+    /// the _argv data area_ and identical environment variables share a single
+    /// copy in the _envv data area_.
+    ///
+    /// The entries of the duplicates point to the same data, which reduces the
+    /// size of the layout.
+    #[must_use]
+    pub const fn with_string_pool(mut self) -> Self {
+        self.string_pool = true;
+        self
+    }
+
+    /// Sets the alignment of the start of the layout for
+    /// [`Self::build_on_stack`] and [`Self::build_on_slice`].
+    ///
+    /// The default is `2 * align_of::<usize>()`, which satisfies the calling
+    /// convention of x86_64. Some architectures or extensions require a larger
+    /// alignment, e.g., 32 bytes for AVX-512.
+    ///
+    /// # Panics
+    /// Panics if `align` is not a power of two or smaller than
+    /// `align_of::<usize>()`.
+    #[must_use]
+    pub const fn with_stack_alignment(mut self, align: usize) -> Self {
+        assert!(
+            align.is_power_of_two() && align >= align_of::<usize>(),
+            "stack alignment must be a power of two and at least align_of::<usize>()"
+        );
+        self.stack_alignment = Some(align);
+        self
+    }
+
+    /// Pads the space between the entries and the data areas with zero bytes,
+    /// so that the data areas start at an offset within the layout that is a
+    /// multiple of `align`, e.g., `4096` for a page-aligned data area.
+    ///
+    /// The address of the data areas is aligned as well if the layout is
+    /// built at an address with this alignment, e.g., by
+    /// [`Self::build_at_address`]. By default, the data areas immediately
+    /// follow the entries.
+    ///
+    /// # Panics
+    /// Panics if `align` is not a power of two.
+    #[must_use]
+    pub const fn with_data_area_alignment(mut self, align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "data area alignment must be a power of two"
+        );
+        self.data_area_alignment = Some(align);
+        self
+    }
+
+    /// Returns the alignment of the start of the layout on a stack.
+    const fn stack_alignment(&self) -> usize {
+        match self.stack_alignment {
+            Some(align) => align,
+            None => align_of::<usize>() * 2,
+        }
+    }
+
+    /// Returns the size in bytes of a word of the target.
+    const fn word_size(&self) -> usize {
+        match self.arch {
+            Some(arch) => arch.word_size(),
+            None => size_of::<usize>(),
+        }
+    }
+
+    /// Returns the size in bytes of a single `argv` entry of the target, i.e.,
+    /// [`WORD_SIZE`](crate::WORD_SIZE) unless a different [`Arch`] is set by
+    /// [`Self::with_arch`].
+    #[must_use]
+    pub const fn argv_entry_size(&self) -> usize {
+        self.word_size()
+    }
+
+    /// Like [`Self::argv_entry_size`] but for `envv` entries.
+    #[must_use]
+    pub const fn envv_entry_size(&self) -> usize {
+        self.word_size()
+    }
+
+    /// Returns the size in bytes of a single `auxv` entry (type and value) of
+    /// the target, i.e., [`AUXVAR_RAW_SIZE`](crate::AUXVAR_RAW_SIZE) unless a
+    /// different [`Arch`] is set by [`Self::with_arch`].
+    #[must_use]
+    pub const fn auxv_entry_size(&self) -> usize {
+        2 * self.word_size()
     }
 
     /// Returns the size in bytes needed for the `argv` entries.
     ///
     /// This includes the terminating null entry.
     fn calc_len_argv_entries(&self) -> usize {
-        (self.argv.len() + 1/* null */) * size_of::<usize>()
+        (self.argv.len() + 1/* null */) * self.argv_entry_size()
     }
 
     /// Returns the size in bytes needed for the `envv` entries.
     ///
     /// This includes the terminating null entry.
     fn calc_len_envv_entries(&self) -> usize {
-        (self.envv.len() + 1/* null */) * size_of::<usize>()
+        (self.envv.len() + 1/* null */) * self.envv_entry_size()
     }
 
     /// Returns the size in bytes needed for the `auxv` entries.
     ///
     /// This includes the terminating null entry.
     fn calc_len_auxv_entries(&self) -> usize {
-        (self.auxv.len() + 1/* NULL entry */) * size_of::<AuxVarRaw>()
+        (self.auxv.len() + self.auxv_raw.len() + 1/* NULL entry */) * self.auxv_entry_size()
+    }
+
+    /// Returns the size in bytes of `argc` and all entries.
+    fn calc_len_entries(&self) -> usize {
+        self.word_size() /* argc */
+            + self.calc_len_argv_entries()
+            + self.calc_len_envv_entries()
+            + self.calc_len_auxv_entries()
     }
 
-    fn _calc_len_data_cstr(strs: &[String]) -> usize {
+    /// Returns the number of padding bytes between the entries and the data
+    /// areas.
+    fn calc_len_data_padding(&self) -> usize {
+        let entries_len = self.calc_len_entries();
+        self.data_area_alignment
+            .map_or(0, |align| entries_len.next_multiple_of(align) - entries_len)
+    }
+
+    fn _calc_len_data_cstr(strs: &[Vec<u8>], string_pool: bool) -> usize {
+        let mut pool = BTreeSet::new();
         strs.iter()
-            .map(|arg| arg.as_bytes())
+            .filter(|bytes| !string_pool || pool.insert(bytes.as_slice()))
             .map(|bytes| CStr::from_bytes_until_nul(bytes).expect("should have NUL byte"))
             .map(|cstr| cstr.count_bytes() + 1 /* NUL */)
             .sum::<usize>()
@@ -143,14 +1012,14 @@ impl<'a> StackLayoutBuilder<'a> {
     ///
     /// This includes any terminating null entries or padding.
     fn calc_len_argv_data(&self) -> usize {
-        Self::_calc_len_data_cstr(&self.argv)
+        Self::_calc_len_data_cstr(&self.argv, self.string_pool)
     }
 
     /// Returns the size in bytes needed for the `envv` data area.
     ///
     /// This includes any terminating null entries or padding.
     fn calc_len_envv_data(&self) -> usize {
-        Self::_calc_len_data_cstr(&self.envv)
+        Self::_calc_len_data_cstr(&self.envv, self.string_pool)
     }
 
     /// Returns the size in bytes needed for the `auxv` data area.
@@ -180,67 +1049,288 @@ impl<'a> StackLayoutBuilder<'a> {
             .sum::<usize>()
     }
 
-    /// Returns the total size in bytes needed for the structure.
+    /// Returns the size in bytes of the arguments added so far, including
+    /// their NUL bytes.
     ///
-    /// This includes any null entries or padding.
-    fn calc_total_len(&self) -> usize {
-        size_of::<usize>() /* argc */ +
-            self.calc_len_argv_entries()
-            + self.calc_len_envv_entries()
-            + self.calc_len_auxv_entries()
-            + self.calc_len_argv_data()
-            + self.calc_len_envv_data()
-            + self.calc_len_auxv_data()
+    /// This is cheaper than [`Self::total_size`] and useful for progress
+    /// reporting. With [`Self::with_string_pool`], duplicates are counted
+    /// once.
+    #[must_use]
+    pub fn argv_data_len_so_far(&self) -> usize {
+        self.calc_len_argv_data()
+    }
+
+    /// Like [`Self::argv_data_len_so_far`] but for the environment variables.
+    #[must_use]
+    pub fn envv_data_len_so_far(&self) -> usize {
+        self.calc_len_envv_data()
+    }
+
+    /// Like [`Self::argv_data_len_so_far`] but for the payloads of the
+    /// [`AuxVar`]s, i.e., the strings including their NUL bytes and the
+    /// random bytes.
+    #[must_use]
+    pub fn auxv_data_len_so_far(&self) -> usize {
+        self.calc_len_auxv_data()
+    }
+
+    /// Returns the total size in bytes needed for the structure.
+    ///
+    /// This includes any null entries or padding and equals the length of the
+    /// buffer returned by [`Self::build`].
+    #[must_use]
+    pub fn total_size(&self) -> usize {
+        self.calc_len_entries()
+            + self.calc_len_data_padding()
+            + self.calc_len_argv_data()
+            + self.calc_len_envv_data()
+            + self.calc_len_auxv_data()
+    }
+
+    /// Returns a compact one-line summary of the layout for logging, such as
+    /// `StackLayout(argc=2, envc=4, auxvc=7, total=512B)`, without building
+    /// it.
+    ///
+    /// The counts exclude the terminating null entries and `total` is
+    /// [`Self::total_size`].
+    #[must_use]
+    pub fn layout_summary(&self) -> String {
+        format!(
+            "StackLayout(argc={}, envc={}, auxvc={}, total={}B)",
+            self.argc_override.unwrap_or(self.argv.len()),
+            self.envv.len(),
+            self.auxv.len() + self.auxv_raw.len(),
+            self.total_size()
+        )
+    }
+
+    /// Returns where each string will be placed in the buffer returned by
+    /// [`Self::build`], without building it.
+    ///
+    /// With [`Self::with_string_pool`], duplicates share the offset of their
+    /// first occurrence.
+    #[must_use]
+    pub fn compute_data_map(&self) -> DataMap {
+        fn map_cstrs(
+            strs: &[Vec<u8>],
+            string_pool: bool,
+            offset: &mut usize,
+        ) -> Vec<(usize, usize)> {
+            let mut pool = BTreeMap::<&[u8], usize>::new();
+            strs.iter()
+                .map(|bytes| {
+                    let cstr = CStr::from_bytes_until_nul(bytes).expect("should have NUL byte");
+                    let len = cstr.count_bytes() + 1 /* NUL */;
+                    if let Some(&start) = pool.get(bytes.as_slice()) {
+                        return (start, len);
+                    }
+                    let start = *offset;
+                    *offset += len;
+                    if string_pool {
+                        pool.insert(bytes, start);
+                    }
+                    (start, len)
+                })
+                .collect()
+        }
+
+        // auxv data area comes first, then argv, then envv
+        let mut offset = self.calc_len_entries() + self.calc_len_data_padding();
+
+        let mut auxv = self.auxv.iter().collect::<Vec<_>>();
+        if self.sort_auxv {
+            auxv.sort_by_key(|aux| aux.key().val());
+        }
+        let auxv_strings = auxv
+            .into_iter()
+            .filter_map(|aux| {
+                let len = match aux {
+                    AuxVar::Platform(v) | AuxVar::BasePlatform(v) | AuxVar::ExecFn(v) => {
+                        v.count_bytes() + 1 /* NUL */
+                    }
+                    AuxVar::Random(_) => 16, /* fixed size */
+                    _ => return None,
+                };
+                let start = offset;
+                offset += len;
+                Some((aux.key(), start, len))
+            })
+            .collect();
+        let argv_strings = map_cstrs(&self.argv, self.string_pool, &mut offset);
+        let envv_strings = map_cstrs(&self.envv, self.string_pool, &mut offset);
+        debug_assert_eq!(offset, self.total_size());
+
+        DataMap {
+            argv_strings,
+            envv_strings,
+            auxv_strings,
+        }
     }
 
     /// Builds the layout with heap-allocated memory.
+    ///
+    /// # Panics
+    /// Panics if the address of the buffer doesn't fit into the word size of
+    /// the [`Arch`] set by [`Self::with_arch`].
+    #[must_use]
+    pub fn build(self) -> ABox<[u8]> {
+        self.build_with_target_addr(None)
+    }
+
+    /// Like [`Self::build`] but the pointers in the layout are calculated
+    /// relative to `base_addr` instead of the address of the buffer.
+    ///
+    /// This is useful if the layout is copied to a known address afterwards,
+    /// for example, into the address space of another process or a fixed
+    /// kernel stack. Unlike [`Self::build_on_stack`], the memory at
+    /// `base_addr` is never accessed.
+    ///
+    /// # Panics
+    /// Panics if the pointers don't fit into the word size of the [`Arch`] set
+    /// by [`Self::with_arch`].
+    #[must_use]
+    pub fn build_at_address(self, base_addr: usize) -> ABox<[u8]> {
+        self.build_with_target_addr(Some(base_addr))
+    }
+
+    /// Builds the entries and the data areas of the layout separately.
+    ///
+    /// The first buffer contains `argc` and the entries of `argv`, `envv`, and
+    /// `auxv`. The second buffer contains the data areas, i.e., the strings and
+    /// the payloads of the [`AuxVar`]s. All pointers in the entries reference
+    /// the data as if the second buffer is placed at `data_base`.
+    ///
+    /// This is useful for loaders that manage the data in a different memory
+    /// region than the entries.
+    ///
+    /// # Panics
+    /// Panics if the pointers don't fit into the word size of the [`Arch`] set
+    /// by [`Self::with_arch`].
+    #[must_use]
+    pub fn build_no_data_area(self, data_base: usize) -> (ABox<[u8]>, Vec<u8>) {
+        let entries_len = self.calc_len_entries();
+        let align = align_of::<usize>().max(self.word_size());
+
+        let buffer = self.build_with_target_addr(Some(data_base.wrapping_sub(entries_len)));
+        let (entries, data) = buffer.split_at(entries_len);
+        (
+            AVec::from_slice(align, entries).into_boxed_slice(),
+            data.to_vec(),
+        )
+    }
+
+    /// Builds only `argc` and the entries of `argv`, `envv`, and `auxv` without
+    /// any data area, e.g., for fast test setups that only need correct entry
+    /// counts.
+    ///
+    /// All pointers are sentinel values `0xDEAD_0000 + index * 0x1000`, where
+    /// `index` counts the pointers over `argv`, `envv`, and the [`AuxVar`]s
+    /// with payloads. Immediate values of the [`AuxVar`]s are written as is.
+    ///
+    /// The result is invalid for real use: the pointers must never be
+    /// dereferenced.
+    ///
+    /// # Panics
+    /// Panics if a value doesn't fit into the word size of the [`Arch`] set by
+    /// [`Self::with_arch`], or if a sentinel value doesn't fit into a `usize`
+    /// of the host, i.e., after about 8.5k pointers on 32-bit hosts.
     #[must_use]
-    pub fn build(mut self) -> ABox<[u8]> {
-        if Some(&AuxVar::Null) != self.auxv.last() {
-            self.auxv.push(AuxVar::Null);
+    pub fn build_minimal(mut self) -> ABox<[u8]> {
+        const SENTINEL_BASE: usize = 0xDEAD_0000;
+        const SENTINEL_STRIDE: usize = 0x1000;
+
+        if self.sort_auxv {
+            self.auxv.sort_by_key(|aux| aux.key().val());
         }
 
-        // Zeroed buffer. Enables us to not write dedicated NULL entries into
-        // `argv` and `envv`.
-        let mut buffer = {
-            let len = self.calc_total_len();
-            let mut vec = AVec::<u8>::new(align_of::<usize>());
-            for _ in 0..len {
-                vec.push(0);
-            }
-            vec.into_boxed_slice()
-        };
+        let mut buffer = self.zeroed_buffer(self.calc_len_entries());
 
         let mut serializer = StackLayoutSerializer::new(
             &mut buffer,
+            self.word_size(),
             self.calc_len_argv_entries(),
             self.calc_len_envv_entries(),
             self.calc_len_auxv_entries(),
-            self.calc_len_argv_data(),
-            self.calc_len_envv_data(),
-            self.calc_len_auxv_data(),
+            0,
+            0,
+            0,
+            0,
         );
-        serializer.write_argc(self.argv.len());
-
-        for arg in self.argv {
-            let c_str = CStr::from_bytes_until_nul(arg.as_bytes()).unwrap();
-            serializer.write_arg(c_str);
+        // The sentinels are written as offsets relative to address zero.
+        serializer.set_target_addr(0);
+        let mut sentinels = (0..).map(|index: usize| {
+            index
+                .checked_mul(SENTINEL_STRIDE)
+                .and_then(|offset| offset.checked_add(SENTINEL_BASE))
+                .expect("sentinel value should fit into usize")
+        });
+
+        serializer.write_argc(self.argc_override.unwrap_or(self.argv.len()));
+        for _ in &self.argv {
+            serializer.write_arg_ref(sentinels.next().unwrap());
         }
-        // Writing NULL entry not necessary, the buffer is already zeroed
-
-        for var in self.envv {
-            let c_str = CStr::from_bytes_until_nul(var.as_bytes()).unwrap();
-            serializer.write_env(c_str);
+        for _ in &self.envv {
+            serializer.write_env_ref(sentinels.next().unwrap());
         }
-        // Writing NULL entry not necessary, the buffer is already zeroed
+        for aux in &self.auxv {
+            let value = if aux.key().value_in_data_area() {
+                sentinels.next().unwrap()
+            } else {
+                aux.value_raw()
+            };
+            serializer.write_aux_immediate(aux.key(), value);
+        }
+        for raw in &self.auxv_raw {
+            serializer.write_aux_raw(raw.key_raw(), raw.value());
+        }
+        // Writing NULL entries not necessary, the buffer is already zeroed
 
-        for var in self.auxv {
-            serializer.write_aux(&var);
+        buffer
+    }
+
+    /// Returns a zeroed buffer of `len` bytes that is aligned for words of the
+    /// target.
+    ///
+    /// Being zeroed enables us to not write dedicated NULL entries into
+    /// `argv` and `envv`.
+    fn zeroed_buffer(&self, len: usize) -> ABox<[u8]> {
+        let mut vec = AVec::<u8>::new(align_of::<usize>().max(self.word_size()));
+        for _ in 0..len {
+            vec.push(0);
         }
+        vec.into_boxed_slice()
+    }
 
+    /// Like [`Self::build`] but the pointers are calculated relative to
+    /// `target_addr` instead of the address of the buffer, if present.
+    fn build_with_target_addr(self, target_addr: Option<usize>) -> ABox<[u8]> {
+        let mut buffer = self.zeroed_buffer(self.total_size());
+        self.serialize(&mut buffer, target_addr);
         buffer
     }
 
+    /// Like [`Self::build`] but describes every write of the serialization
+    /// by a human-readable line in `writer`, e.g.,
+    /// `write_arg("./prog") at entry_offset=8, data_offset=256`.
+    ///
+    /// All offsets are in bytes relative to the start of the layout. This
+    /// helps to debug alignment issues.
+    ///
+    /// # Errors
+    /// Returns the first error of `writer`. The layout is built completely
+    /// nevertheless but not returned.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Self::build`].
+    pub fn build_traced<W: core::fmt::Write>(
+        self,
+        mut writer: W,
+    ) -> Result<ABox<[u8]>, core::fmt::Error> {
+        let mut buffer = self.zeroed_buffer(self.total_size());
+        self.serialize_traced(&mut buffer, None, Some(&mut writer))?;
+        Ok(buffer)
+    }
+
     /// Builds the layout on pre-allocated stack memory.
     ///
     /// # Arguments
@@ -251,19 +1341,15 @@ impl<'a> StackLayoutBuilder<'a> {
     /// total size in bytes of the stack layout.
     ///
     #[must_use]
-    pub fn build_on_stack(mut self, stack_top: usize) -> (usize, usize) {
-        if Some(&AuxVar::Null) != self.auxv.last() {
-            self.auxv.push(AuxVar::Null);
-        }
-
-        let len = self.calc_total_len();
+    pub fn build_on_stack(self, stack_top: usize) -> (usize, usize) {
+        let len = self.total_size();
 
-        let (mut buffer, stack_base) = {
+        let (buffer, stack_base) = {
             // If a target address is given, we allocate the buffer with
             // the given alignment.
             // x86_64 calling convention: the stack must be 16-byte aligned before
             // calling a function.
-            let stack_base = (stack_top - len) & !(align_of::<usize>() * 2 - 1);
+            let stack_base = (stack_top - len) & !(self.stack_alignment() - 1);
             let stack_range = unsafe {
                 // Zeroed the buffer.
                 core::ptr::write_bytes(stack_base as *mut u8, 0, len);
@@ -272,238 +1358,205 @@ impl<'a> StackLayoutBuilder<'a> {
             (stack_range, stack_base)
         };
 
+        self.serialize(buffer, None);
+        (stack_base, len)
+    }
+
+    /// Safe alternative to [`Self::build_on_stack`] that builds the layout
+    /// into the end of `stack_mem`, e.g., a fixed-size array or a mapping.
+    ///
+    /// Like for [`Self::build_on_stack`], the start of the layout is aligned
+    /// as configured by [`Self::with_stack_alignment`].
+    ///
+    /// # Returns
+    /// A tuple containing the offset of the layout within `stack_mem` and the
+    /// total size in bytes of the stack layout.
+    ///
+    /// # Panics
+    /// Panics if `stack_mem` is too small to hold the aligned layout.
+    #[must_use]
+    pub fn build_on_slice(self, stack_mem: &mut [u8]) -> (usize, usize) {
+        let len = self.total_size();
+
+        let addr = stack_mem.as_ptr() as usize;
+        let stack_base = (addr + stack_mem.len())
+            .checked_sub(len)
+            .map(|base| base & !(self.stack_alignment() - 1))
+            .filter(|&base| base >= addr)
+            .expect("stack memory should be large enough for the layout");
+        let offset = stack_base - addr;
+
+        let buffer = &mut stack_mem[offset..offset + len];
+        buffer.fill(0);
+        self.serialize(buffer, None);
+        (offset, len)
+    }
+
+    /// Builds the layout and writes it to the piped stdin of `child`, which is
+    /// closed afterwards.
+    ///
+    /// This enables testing programs that parse a stack layout, e.g., dynamic
+    /// linkers under test. The child must be spawned with
+    /// [`Stdio::piped`] stdin; the caller remains responsible for waiting for
+    /// it.
+    ///
+    /// # Returns
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    /// Returns [`io::ErrorKind::InvalidInput`] if stdin of the child is not
+    /// piped or was already taken, and any error of writing to the pipe.
+    ///
+    /// [`Stdio::piped`]: std::process::Stdio::piped
+    #[cfg(feature = "std")]
+    pub fn into_child_stdin(self, child: &mut Child) -> io::Result<usize> {
+        let mut stdin = child.stdin.take().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "stdin of the child is not piped",
+            )
+        })?;
+        let buffer = self.build();
+        stdin.write_all(&buffer)?;
+        Ok(buffer.len())
+    }
+
+    /// Serializes the layout into the zeroed `buffer`.
+    fn serialize(self, buffer: &mut [u8], target_addr: Option<usize>) {
+        self.serialize_traced(buffer, target_addr, None)
+            .expect("should not fail without tracer");
+    }
+
+    /// Like [`Self::serialize`] but reports every write to `tracer`, if
+    /// present.
+    fn serialize_traced(
+        mut self,
+        buffer: &mut [u8],
+        target_addr: Option<usize>,
+        tracer: Option<&mut dyn core::fmt::Write>,
+    ) -> core::fmt::Result {
+        if self.sort_auxv {
+            self.auxv.sort_by_key(|aux| aux.key().val());
+        }
+
+        let total_size = self.total_size();
         let mut serializer = StackLayoutSerializer::new(
-            &mut buffer,
+            buffer,
+            self.word_size(),
             self.calc_len_argv_entries(),
             self.calc_len_envv_entries(),
             self.calc_len_auxv_entries(),
+            self.calc_len_data_padding(),
             self.calc_len_argv_data(),
             self.calc_len_envv_data(),
             self.calc_len_auxv_data(),
         );
+        if let Some(target_addr) = target_addr {
+            serializer.set_target_addr(target_addr);
+        }
+        if let Some(tracer) = tracer {
+            serializer.set_tracer(tracer);
+        }
 
-        serializer.write_argc(self.argv.len());
-
-        for arg in self.argv {
-            let c_str = CStr::from_bytes_until_nul(arg.as_bytes()).unwrap();
-            serializer.write_arg(c_str);
+        serializer.write_argc(self.argc_override.unwrap_or(self.argv.len()));
+
+        // Offsets of the already written data of each string
+        let mut pool = BTreeMap::<&[u8], usize>::new();
+        for arg in &self.argv {
+            match pool.get(arg.as_slice()) {
+                Some(&data_offset) => serializer.write_arg_ref(data_offset),
+                None => {
+                    let c_str = CStr::from_bytes_until_nul(arg).unwrap();
+                    let data_offset = serializer.write_arg(c_str);
+                    if self.string_pool {
+                        pool.insert(arg, data_offset);
+                    }
+                }
+            }
         }
         // Writing NULL entry not necessary, the buffer is already zeroed
 
-        for var in self.envv {
-            let c_str = CStr::from_bytes_until_nul(var.as_bytes()).unwrap();
-            serializer.write_env(c_str);
+        pool.clear();
+        for var in &self.envv {
+            match pool.get(var.as_slice()) {
+                Some(&data_offset) => serializer.write_env_ref(data_offset),
+                None => {
+                    let c_str = CStr::from_bytes_until_nul(var).unwrap();
+                    let data_offset = serializer.write_env(c_str);
+                    if self.string_pool {
+                        pool.insert(var, data_offset);
+                    }
+                }
+            }
         }
         // Writing NULL entry not necessary, the buffer is already zeroed
 
         for var in self.auxv {
             serializer.write_aux(&var);
         }
+        for raw in &self.auxv_raw {
+            serializer.write_aux_raw(raw.key_raw(), raw.value());
+        }
+        // Writing NULL entry not necessary, the buffer is already zeroed
 
-        (stack_base, len)
+        // `total_size()` must match what is actually written.
+        debug_assert_eq!(serializer.data_end(), total_size);
+        serializer.trace_result()
     }
 }
 
-/// Serializer for [`StackLayoutBuilder`].
-///
-/// This type takes care of the _entry area_ and the _data area_ with respect
-/// to a given `target_addr` (base address in target address space).
-///
-/// All strings can contain a NUL byte already. If it is not present, the
-/// serializer will take care of that.
-struct StackLayoutSerializer<'a> {
-    buffer: &'a mut [u8],
-    // Offset in bytes for writes
-    offset_argv: usize,
-    // Offset in bytes for writes
-    offset_envv: usize,
-    // Offset in bytes for writes
-    offset_auxv: usize,
-    // Offset in bytes for writes
-    offset_argv_data: usize,
-    // Offset in bytes for writes
-    offset_envv_data: usize,
-    // Offset in bytes for writes
-    offset_auxv_data: usize,
-}
-
-impl<'a> StackLayoutSerializer<'a> {
-    /// Creates a new builder.
-    ///
-    /// The `auxv` entries [`AuxVarType::Null`] will be added automatically.
-    ///
-    /// # Arguments
-    /// - `target_addr`: The address the stack layout in the target address space.
-    ///   This may be a user-space address of another process.
-    #[allow(clippy::too_many_arguments)]
-    fn new(
-        buffer: &'a mut [u8],
-        len_argv_entries: usize,
-        len_envv_entries: usize,
-        len_auxv_entries: usize,
-        len_argv_data: usize,
-        len_envv_data: usize,
-        len_auxv_data: usize,
-    ) -> Self {
-        assert_eq!(buffer.as_ptr().align_offset(align_of::<usize>()), 0);
-
-        let total_size = size_of::<usize>() /* initial argc */ + len_argv_entries + len_envv_entries + len_auxv_entries
-            + len_argv_data + len_envv_data + len_auxv_data;
-        assert!(buffer.len() >= total_size);
-
-        // These offsets include any necessary NULL entries and NUL bytes.
-        let offset_argv = size_of::<usize>() /* initial argc */;
-        let offset_envv = offset_argv + len_argv_entries;
-        let offset_auxv = offset_envv + len_envv_entries;
-        // auxv data area comes first, then argv, then envv
-        let offset_auxv_data = offset_auxv + len_auxv_entries;
-        let offset_argv_data = offset_auxv_data + len_auxv_data;
-        let offset_envv_data = offset_argv_data + len_argv_data;
-
-        Self {
-            buffer,
-            offset_argv: size_of::<usize>(), /* argc */
-            offset_envv,
-            offset_auxv,
-            offset_argv_data,
-            offset_envv_data,
-            offset_auxv_data,
-        }
-    }
-
-    /// Performs sanity checks ensuring that no offset breaks its boundaries.
-    fn sanity_checks(&self) {
-        assert!(self.offset_argv <= self.offset_envv);
-        assert!(self.offset_envv <= self.offset_auxv);
-        assert!(self.offset_auxv <= self.offset_auxv_data);
-        assert!(self.offset_auxv_data <= self.offset_argv_data);
-        assert!(self.offset_argv_data <= self.offset_envv_data);
-        assert!(self.offset_envv_data <= self.buffer.len());
-    }
-
-    /// Writes bytes to the data area and updates the offset afterward.
-    const fn _write_data_area(buffer: &mut [u8], data: &[u8], data_area_offset: &mut usize) {
-        let src_ptr = data.as_ptr();
-        let dst_ptr = buffer.as_mut_ptr().cast::<u8>();
-        let dst_ptr = unsafe { dst_ptr.add(*data_area_offset) };
-        unsafe {
-            core::ptr::copy_nonoverlapping(src_ptr, dst_ptr, data.len());
-        }
-        *data_area_offset += data.len();
-    }
-
-    /// Writes a null-terminated CStr into the structure, including the
-    /// pointer and the actual data.
-    fn _write_cstr(
-        buffer: &mut [u8],
-        str: &CStr,
-        entry_offset: &mut usize,
-        data_area_offset: &mut usize,
-    ) {
-        // The address where this will be reachable from a user-perspective.
-        let data_addr = buffer.as_ptr() as *const _ as usize + *data_area_offset;
-
-        // write entry
-        {
-            let src_ptr = buffer.as_mut_ptr().cast::<u8>();
-            let src_ptr = unsafe { src_ptr.add(*entry_offset) };
-            unsafe { core::ptr::write(src_ptr.cast::<usize>(), data_addr) }
-            *entry_offset += size_of::<usize>();
+impl Debug for StackLayoutBuilder<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        fn as_cstrs(strs: &[Vec<u8>]) -> Vec<&CStr> {
+            strs.iter()
+                .map(|str| CStr::from_bytes_with_nul(str).expect("should be NUL-terminated"))
+                .collect()
         }
 
-        // write data
-        Self::_write_data_area(buffer, str.to_bytes(), data_area_offset);
-        // write NUL
-        Self::_write_data_area(buffer, &[0], data_area_offset);
-    }
-
-    /// Writes the `argc` value into the structure.
-    fn write_argc(&mut self, argc: usize) {
-        unsafe { core::ptr::write(self.buffer.as_mut_ptr().cast::<usize>(), argc) }
-
-        self.sanity_checks();
-    }
-
-    /// Writes an argument into the structure.
-    fn write_arg(&mut self, arg: &CStr) {
-        Self::_write_cstr(
-            self.buffer,
-            arg,
-            &mut self.offset_argv,
-            &mut self.offset_argv_data,
-        );
-        self.sanity_checks();
-    }
-
-    /// Writes an environmental variable into the structure.
-    fn write_env(&mut self, var: &CStr) {
-        Self::_write_cstr(
-            self.buffer,
-            var,
-            &mut self.offset_envv,
-            &mut self.offset_envv_data,
-        );
-
-        self.sanity_checks();
-    }
-
-    /// Writes an auxiliary variable into the auxiliary vector.
-    fn write_aux_immediate(&mut self, key: AuxVarType, val: usize) {
-        let ptr = self.buffer.as_mut_ptr().cast::<u8>();
-        let ptr = unsafe { ptr.add(self.offset_auxv) };
-        let value = AuxVarRaw::new(key, val);
-        unsafe { core::ptr::write(ptr.cast::<AuxVarRaw>(), value) }
-        self.offset_auxv += size_of::<AuxVarRaw>();
+        f.debug_struct("StackLayoutBuilder")
+            .field("argc", &self.argc_override.unwrap_or(self.argv.len()))
+            .field("envc", &self.envv.len())
+            .field("auxvc", &(self.auxv.len() + self.auxv_raw.len()))
+            .field("total_size", &self.total_size())
+            .field("argv", &as_cstrs(&self.argv))
+            .field("envv", &as_cstrs(&self.envv))
+            .field("auxv", &self.auxv)
+            .field("auxv_raw", &self.auxv_raw)
+            .field("enforce_linux_limits", &self.enforce_linux_limits)
+            .field("envv_key_policy", &self.envv_key_policy)
+            .field("arch", &self.arch)
+            .field("sort_auxv", &self.sort_auxv)
+            .field("string_pool", &self.string_pool)
+            .field("stack_alignment", &self.stack_alignment())
+            .field("argv_dedup", &self.argv_dedup)
+            .field("data_area_alignment", &self.data_area_alignment)
+            .finish()
     }
+}
 
-    /// Writes the referenced data of an auxiliary vector into the
-    /// _auxv data area_.
-    fn write_aux_refdata(&mut self, key: AuxVarType, data: &[u8], add_nul_byte: bool) {
-        // The address where this will be reachable from a user-perspective.
-        let data_addr = self.buffer.as_ptr() as *const _ as usize + self.offset_auxv_data;
-        self.write_aux_immediate(key, data_addr);
-
-        // write data
-        Self::_write_data_area(self.buffer, data, &mut self.offset_auxv_data);
-
-        // add NUL byte if necessary
-        if add_nul_byte {
-            // assert: If there is a NUL byte in the data, it is only allowed at
-            // the very last position
-            if let Some(pos) = get_null_index(data) {
-                assert_eq!(
-                    pos,
-                    data.len() - 1,
-                    "strings must not contain interim NUL bytes"
-                );
-            }
-
-            if data.last().copied().unwrap() != 0 {
-                // write NUL
-                Self::_write_data_area(self.buffer, &[0], &mut self.offset_auxv_data);
-            }
+impl<'a> Extend<AuxVar<'a>> for StackLayoutBuilder<'a> {
+    /// Adds all [`AuxVar`]s via [`StackLayoutBuilder::add_auxv`].
+    fn extend<T: IntoIterator<Item = AuxVar<'a>>>(&mut self, iter: T) {
+        for aux in iter {
+            self.add_auxv(aux);
         }
     }
+}
 
-    /// Deconstructs a [`AuxVar`] and writes the corresponding [`AuxVarRaw`]
-    /// into the structure.
-    fn write_aux(&mut self, aux: &AuxVar<'a>) {
-        match aux {
-            AuxVar::Platform(v) => self.write_aux_refdata(aux.key(), v.as_bytes(), true),
-            AuxVar::BasePlatform(v) => self.write_aux_refdata(aux.key(), v.as_bytes(), true),
-            AuxVar::Random(v) => self.write_aux_refdata(aux.key(), v, false),
-            AuxVar::ExecFn(v) => self.write_aux_refdata(aux.key(), v.as_bytes(), true),
-            _ => self.write_aux_immediate(aux.key(), aux.value_raw()),
-        }
-
-        self.sanity_checks();
+impl<'a> FromIterator<AuxVar<'a>> for StackLayoutBuilder<'a> {
+    /// Creates a builder without arguments and environment variables with the
+    /// given [`AuxVar`]s.
+    fn from_iter<T: IntoIterator<Item = AuxVar<'a>>>(iter: T) -> Self {
+        let mut builder = Self::new();
+        builder.extend(iter);
+        builder
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::StackLayoutRef;
+    use crate::{AuxVarRaw, StackLayoutRef};
 
     #[test]
     fn test_builder() {
@@ -579,4 +1632,1037 @@ mod tests {
         let at_base_platform = fn_get_at_string(AuxVarType::BasePlatform);
         assert_eq!(at_base_platform, "Base Platform as &str");
     }
+
+    #[test]
+    fn test_builder_new_const() {
+        const BUILDER: StackLayoutBuilder<'static> = StackLayoutBuilder::new();
+
+        assert_eq!(BUILDER, StackLayoutBuilder::default());
+        assert_eq!(BUILDER.total_size(), size_of::<usize>() * 5);
+    }
+
+    #[test]
+    fn test_builder_into_owned() {
+        let platform = String::from("x86_64");
+        let exec_fn = alloc::ffi::CString::from(c"./prog");
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Platform(platform.as_str().into()));
+        builder.add_auxv(AuxVar::ExecFn(exec_fn.as_c_str().into()));
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        let shallow = builder.clone();
+        let owned = builder.into_owned();
+        assert_eq!(owned.total_size(), shallow.total_size());
+        drop(shallow);
+        drop(platform);
+        drop(exec_fn);
+
+        let buffer = owned.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        // SAFETY: This was created for the address space of this process.
+        let auxv = unsafe { layout.auxv_iter() }.collect::<Vec<_>>();
+        assert_eq!(
+            auxv,
+            [
+                AuxVar::Platform(c"x86_64".into()),
+                AuxVar::ExecFn(c"./prog".into()),
+                AuxVar::Pagesz(4096),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_program_name() {
+        let mut builder = StackLayoutBuilder::new();
+        assert_eq!(builder.program_name(), None);
+
+        builder.set_program_name("/bin/foo");
+        assert_eq!(builder.program_name(), Some("/bin/foo"));
+
+        builder.add_argv("--help");
+        builder.set_program_name("/bin/bar\0");
+        assert_eq!(builder.program_name(), Some("/bin/bar"));
+
+        let layout = builder.build();
+        let layout = StackLayoutRef::new(layout.as_ref(), None);
+        assert_eq!(layout.argc(), 2);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            let argv = layout.argv_iter().collect::<Vec<_>>();
+            assert_eq!(argv, [c"/bin/bar", c"--help"]);
+        }
+    }
+
+    #[test]
+    #[should_panic = "the program name must not be empty"]
+    fn test_builder_program_name_empty() {
+        StackLayoutBuilder::new().set_program_name("");
+    }
+
+    #[test]
+    fn test_builder_override_argc() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("a");
+        builder.add_argv("b");
+        builder.add_argv("c");
+        builder.override_argc(0);
+        let buffer = builder.build();
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        assert_eq!(layout.argc(), 0);
+        // The entries are still present.
+        let layout = StackLayoutRef::new(&buffer[size_of::<usize>()..], Some(3));
+        assert_eq!(layout.argv_raw_iter().len(), 3);
+    }
+
+    #[test]
+    fn test_builder_at_address() {
+        const BASE_ADDR: usize = 0x7fff_0000;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Platform("x86_64".into()));
+        let buffer = builder.build_at_address(BASE_ADDR);
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        let cstr_at = |addr: usize| {
+            let offset = addr - BASE_ADDR;
+            CStr::from_bytes_until_nul(&buffer[offset..]).unwrap()
+        };
+        let argv = layout.argv_raw_iter().map(|ptr| cstr_at(ptr as usize));
+        assert!(argv.eq([c"./prog"]));
+        let envv = layout.envv_raw_iter().map(|ptr| cstr_at(ptr as usize));
+        assert!(envv.eq([c"FOO=bar"]));
+        let platform = layout.auxv_raw_iter().next().unwrap();
+        assert_eq!(cstr_at(platform.value()), c"x86_64");
+    }
+
+    #[test]
+    fn test_builder_layout_summary() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("-v");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv_raw(AuxVarRaw::new(AuxVarType::Uid, 1000));
+        let summary = builder.layout_summary();
+
+        let fields = summary
+            .strip_prefix("StackLayout(")
+            .and_then(|s| s.strip_suffix("B)"))
+            .unwrap()
+            .split(", ")
+            .map(|field| {
+                let (key, value) = field.split_once('=').unwrap();
+                (key, value.parse::<usize>().unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                ("argc", 2),
+                ("envc", 1),
+                ("auxvc", 2),
+                ("total", builder.total_size())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_compute_data_map() {
+        let mut builder = StackLayoutBuilder::new()
+            .with_string_pool()
+            .with_data_area_alignment(16);
+        builder.add_argv("./prog");
+        builder.add_argv("-v");
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::ExecFn("./exec".into()));
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        let map = builder.compute_data_map();
+        assert_eq!(map.argv_strings[0], map.argv_strings[2]);
+        assert_eq!(map.argv_strings[1].1, 3);
+
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        let base = buffer.as_ptr() as usize;
+        let offsets = |entries: &[usize]| {
+            entries
+                .iter()
+                .take_while(|&&ptr| ptr != 0)
+                .map(|ptr| ptr - base)
+                .collect::<Vec<_>>()
+        };
+        let starts = |strings: &[(usize, usize)]| {
+            strings.iter().map(|&(start, _)| start).collect::<Vec<_>>()
+        };
+        assert_eq!(map.argv_strings[0].0, layout.argv_raw_entries()[0] - base);
+        assert_eq!(
+            offsets(layout.argv_raw_entries()),
+            starts(&map.argv_strings)
+        );
+        assert_eq!(
+            offsets(layout.envv_raw_entries()),
+            starts(&map.envv_strings)
+        );
+
+        let auxv = layout
+            .auxv_raw_iter()
+            .filter(|aux| aux.key().is_ok_and(|key| key.value_in_data_area()))
+            .map(|aux| aux.value() - base);
+        assert!(auxv.eq(map.auxv_strings.iter().map(|&(_, start, _)| start)));
+        let (key, start, len) = map.auxv_strings[0];
+        assert_eq!(key, AuxVarType::ExecFn);
+        assert_eq!(&buffer[start..start + len], b"./exec\0");
+        assert_eq!(map.auxv_strings[1].2, 16);
+        let (start, len) = map.envv_strings[0];
+        assert_eq!(start + len, buffer.len());
+    }
+
+    #[test]
+    fn test_builder_no_data_area() {
+        const DATA_BASE: usize = 0x4000;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("./exec".into()));
+        let (entries, data) = builder.build_no_data_area(DATA_BASE);
+        assert_eq!(entries.len(), size_of::<usize>() * (1 + 2 + 2 + 6));
+        assert_eq!(data, b"./exec\0./prog\0FOO=bar\0");
+
+        let cstr_at = |addr: usize| CStr::from_bytes_until_nul(&data[addr - DATA_BASE..]).unwrap();
+        let layout = StackLayoutRef::new(entries.as_ref(), None);
+        let argv = layout.argv_raw_iter().map(|ptr| cstr_at(ptr as usize));
+        assert!(argv.eq([c"./prog"]));
+        let envv = layout.envv_raw_iter().map(|ptr| cstr_at(ptr as usize));
+        assert!(envv.eq([c"FOO=bar"]));
+        let exec_fn = layout.auxv_raw_iter().nth(1).unwrap();
+        assert_eq!(cstr_at(exec_fn.value()), c"./exec");
+    }
+
+    #[test]
+    fn test_builder_entry_sizes() {
+        use crate::{AUXVAR_RAW_SIZE, WORD_SIZE};
+
+        assert_eq!(WORD_SIZE, size_of::<usize>());
+        assert_eq!(AUXVAR_RAW_SIZE, size_of::<AuxVarRaw>());
+
+        let builder = StackLayoutBuilder::new();
+        assert_eq!(builder.argv_entry_size(), WORD_SIZE);
+        assert_eq!(builder.envv_entry_size(), WORD_SIZE);
+        assert_eq!(builder.auxv_entry_size(), AUXVAR_RAW_SIZE);
+
+        let builder = StackLayoutBuilder::for_arch(Arch::X86);
+        assert_eq!(builder.argv_entry_size(), 4);
+        assert_eq!(builder.envv_entry_size(), 4);
+        assert_eq!(builder.auxv_entry_size(), 8);
+    }
+
+    #[test]
+    fn test_builder_for_arch() {
+        for (arch, word_size) in [
+            (Arch::X86, 4),
+            (Arch::X86_64, 8),
+            (Arch::AArch32, 4),
+            (Arch::AArch64, 8),
+            (Arch::RiscV32, 4),
+            (Arch::RiscV64, 8),
+            (Arch::Mips32, 4),
+            (Arch::Mips64, 8),
+        ] {
+            let builder = StackLayoutBuilder::for_arch(arch);
+            // argc, argv NULL, envv NULL, auxv AT_NULL pair
+            assert_eq!(builder.total_size(), 5 * word_size, "{arch:?}");
+            assert_eq!(builder.stack_alignment(), arch.stack_alignment());
+            assert_eq!(
+                builder,
+                StackLayoutBuilder::new()
+                    .with_arch(arch)
+                    .with_stack_alignment(arch.stack_alignment())
+            );
+        }
+
+        let mut builder = StackLayoutBuilder::for_arch(Arch::X86);
+        builder.add_argv("./prog");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        let buffer = builder.build_at_address(0x1000);
+        assert_eq!(buffer.len(), 4 * (1 + 2 + 1 + 4) + 7);
+        assert_eq!(buffer[..4], 1_u32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_builder_riscv32() {
+        use crate::StackLayoutRef32;
+
+        const TARGET_ADDR: usize = 0x1000_0000;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let builder = builder.with_arch(Arch::RiscV32);
+        assert_eq!(builder.total_size(), 4 * (1 + 2 + 2 + 6) + 7 + 8 + 7);
+
+        let buffer = builder.build_at_address(TARGET_ADDR);
+        let layout = StackLayoutRef32::new(&buffer);
+        assert_eq!(layout.argc(), 1);
+        assert_eq!(layout.envc(), 1);
+        assert_eq!(layout.auxvc(), 2);
+
+        let cstr_at = |addr: u32| {
+            let offset = addr as usize - TARGET_ADDR;
+            CStr::from_bytes_until_nul(&buffer[offset..]).unwrap()
+        };
+        let argv = layout.argv_raw_iter().map(cstr_at).collect::<Vec<_>>();
+        assert_eq!(argv, [c"./prog"]);
+        let envv = layout.envv_raw_iter().map(cstr_at).collect::<Vec<_>>();
+        assert_eq!(envv, [c"FOO=bar"]);
+
+        let auxv = layout.auxv_raw_iter().collect::<Vec<_>>();
+        assert_eq!(auxv[0], AuxVarRaw::new(AuxVarType::Pagesz, 4096));
+        assert_eq!(auxv[1].key(), Ok(AuxVarType::ExecFn));
+        assert_eq!(cstr_at(auxv[1].value() as u32), c"./prog");
+    }
+
+    #[test]
+    fn test_builder_at_flags() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Flags(AtFlags::empty()));
+        let builder = builder.with_at_flags(AtFlags::PRESERVE_ARGV0);
+        let buffer = builder.build();
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        assert_eq!(layout.auxvc(), 1);
+        let raw = layout.auxv_raw_iter().next().unwrap();
+        assert_eq!(raw.key(), Ok(AuxVarType::Flags));
+        assert_eq!(raw.value(), AtFlags::PRESERVE_ARGV0.bits());
+        // SAFETY: This was created for the address space of this process.
+        let aux = unsafe { layout.auxv_iter() }.next().unwrap();
+        assert_eq!(aux.value_flags(), Some(AtFlags::PRESERVE_ARGV0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_builder_platform_autodetect() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Platform("foo".into()));
+        let buffer = builder.with_platform_autodetect().build();
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        assert_eq!(layout.auxvc(), 1);
+        // SAFETY: This was created for the address space of this process.
+        let aux = unsafe { layout.auxv_iter() }.next().unwrap();
+        assert_eq!(aux.key(), AuxVarType::Platform);
+        let platform = aux.value_payload_str().unwrap();
+        assert_eq!(
+            &platform.as_bytes()[..platform.count_bytes()],
+            std::env::consts::ARCH.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_builder_sort_auxv() {
+        let mut builder_a = StackLayoutBuilder::new();
+        builder_a.add_auxv(AuxVar::Uid(1000));
+        builder_a.add_auxv(AuxVar::ExecFn("./prog".into()));
+        builder_a.add_auxv(AuxVar::Pagesz(4096));
+        let mut builder_b = StackLayoutBuilder::new();
+        builder_b.add_auxv(AuxVar::Pagesz(4096));
+        builder_b.add_auxv(AuxVar::Uid(1000));
+        builder_b.add_auxv(AuxVar::ExecFn("./prog".into()));
+        assert!(!builder_a.auxv_is_sorted());
+        assert!(builder_b.auxv_is_sorted());
+
+        assert_ne!(
+            builder_a.clone().build_at_address(0x1000),
+            builder_b.clone().build_at_address(0x1000)
+        );
+        builder_a.set_auxv_sort_before_build(true);
+        builder_b.set_auxv_sort_before_build(true);
+        assert_eq!(
+            builder_a.build_at_address(0x1000),
+            builder_b.build_at_address(0x1000)
+        );
+    }
+
+    #[test]
+    fn test_builder_kernel_ordering() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::L1iCacheSize(32));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::NotElf(false));
+        builder.add_auxv(AuxVar::Random([0; 16]));
+        builder.add_auxv(AuxVar::SysinfoEhdr(0x1000 as _));
+        builder.add_auxv(AuxVar::HwCap(0x1f));
+        let buffer = builder.with_kernel_ordering().build();
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        let keys = layout
+            .auxv_raw_iter()
+            .map(|aux| aux.key().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                AuxVarType::SysinfoEhdr,
+                AuxVarType::HwCap,
+                AuxVarType::Pagesz,
+                AuxVarType::Random,
+                AuxVarType::ExecFn,
+                AuxVarType::L1iCacheSize,
+                AuxVarType::NotElf,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_envv_key_policy() {
+        let mut builder = StackLayoutBuilder::new();
+        assert_eq!(builder.try_add_envv("1INVALID=value"), Ok(()));
+        assert_eq!(builder.try_add_envv("KEY WITH SPACE=value"), Ok(()));
+
+        builder.set_envv_key_policy(EnvKeyPolicy::PosixStrict);
+        assert_eq!(
+            builder.try_add_envv("1INVALID=value"),
+            Err(EnvError::InvalidKey)
+        );
+        assert_eq!(
+            builder.try_add_envv("KEY WITH SPACE=value"),
+            Err(EnvError::InvalidKey)
+        );
+        assert_eq!(builder.try_add_envv("_VALID_1=value"), Ok(()));
+        assert_eq!(builder.envv.len(), 3);
+    }
+
+    #[test]
+    fn test_builder_envv_from_map() {
+        use std::collections::{BTreeMap, HashMap, HashSet};
+
+        let mut map = HashMap::new();
+        map.insert("PATH", "/bin");
+        map.insert("HOME", "/root");
+        map.insert("EMPTY", "");
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_envv_from_map(&map);
+        builder.add_envv_from_map(BTreeMap::from([("B", "2"), ("A", "1")]));
+        let layout = builder.build();
+        let layout = StackLayoutRef::new(layout.as_ref(), None);
+
+        // SAFETY: This was created for the address space of this process.
+        let envv = unsafe { layout.envv_iter() }
+            .map(|env| env.to_str().unwrap())
+            .collect::<std::vec::Vec<_>>();
+        assert_eq!(envv.len(), 5);
+        assert_eq!(
+            envv[..3].iter().copied().collect::<HashSet<_>>(),
+            HashSet::from(["PATH=/bin", "HOME=/root", "EMPTY="])
+        );
+        assert_eq!(envv[3..], ["A=1", "B=2"]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "add_argv: string \"a\\0b\" contains an interior NUL byte at position 1"
+    )]
+    fn test_builder_interim_nul_argv() {
+        StackLayoutBuilder::new().add_argv("a\0b");
+    }
+
+    #[test]
+    #[should_panic(expected = "add_envv: string \"A=\\0")]
+    fn test_builder_interim_nul_envv() {
+        let mut env = String::from("A=\0");
+        env.extend(core::iter::repeat_n('b', 100));
+        StackLayoutBuilder::new().add_envv(env);
+    }
+
+    #[test]
+    fn test_builder_debug() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        let debug = format!("{builder:?}");
+        assert!(debug.starts_with(&format!(
+            "StackLayoutBuilder {{ argc: 1, envc: 1, auxvc: 1, total_size: {}, argv: [\"./prog\"], envv: [\"FOO=bar\"], ",
+            builder.total_size()
+        )));
+        assert!(debug.contains("total_size:"));
+    }
+
+    #[test]
+    fn test_builder_is_auxv_sorted() {
+        let mut builder = StackLayoutBuilder::new();
+        assert!(builder.is_auxv_sorted());
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Uid(1000));
+        assert!(builder.is_auxv_sorted());
+        builder.add_auxv(AuxVar::Phdr(0x40_0040 as *const u8));
+        assert!(!builder.is_auxv_sorted());
+        assert_eq!(builder.is_auxv_sorted(), builder.auxv_is_sorted());
+    }
+
+    #[test]
+    fn test_builder_envv_unique() {
+        let mut builder = StackLayoutBuilder::new();
+        assert!(builder.is_envv_unique());
+        builder.add_envv("FOO=bar");
+        builder.add_envv("FOOBAR=bar");
+        builder.add_envv("BAR=");
+        assert!(builder.is_envv_unique());
+        builder.add_envv("FOO=baz");
+        assert!(!builder.is_envv_unique());
+    }
+
+    #[test]
+    fn test_builder_argv_duplicates() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--flag");
+        builder.add_argv("-v");
+        builder.add_argv("--flag");
+        builder.add_argv("--flag");
+        assert_eq!(
+            builder.check_argv_duplicates(),
+            [(1, 3, "--flag"), (1, 4, "--flag")]
+        );
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.set_argv_dedup(true);
+        builder.extend_argv(["./prog", "--flag", "--flag"]);
+        assert!(builder.check_argv_duplicates().is_empty());
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        // SAFETY: This was created for the address space of this process.
+        let argv = unsafe { layout.argv_iter() }.collect::<Vec<_>>();
+        assert_eq!(argv, [c"./prog", c"--flag"]);
+    }
+
+    #[test]
+    fn test_builder_build_minimal() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.extend_argv(["./prog", "--flag"]);
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::platform("x86_64"));
+        let size = builder.total_size();
+        let buffer = builder.build_minimal();
+        assert!(buffer.len() < size);
+        assert_eq!(buffer.len(), size_of::<usize>() * (1 + 3 + 2 + 3 * 2));
+
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.argc(), 2);
+        assert_eq!(layout.envc(), 1);
+        assert_eq!(layout.auxvc(), 2);
+        assert!(
+            layout
+                .argv_raw_iter()
+                .chain(layout.envv_raw_iter())
+                .map(|ptr| ptr as usize)
+                .eq([0xDEAD_0000, 0xDEAD_1000, 0xDEAD_2000])
+        );
+        let auxv = layout.auxv_raw_iter().collect::<Vec<_>>();
+        assert_eq!(auxv[0].value(), 4096);
+        assert_eq!(auxv[1].value(), 0xDEAD_3000);
+    }
+
+    #[test]
+    fn test_builder_data_area_alignment() {
+        let mut builder = StackLayoutBuilder::new().with_data_area_alignment(4096);
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        let size = builder.total_size();
+        let buffer = builder.build();
+        assert_eq!(buffer.len(), size);
+
+        let layout = StackLayoutRef::new(&buffer, None);
+        let random = layout.auxv_raw_iter().next().unwrap().value();
+        let data_offset = random - buffer.as_ptr() as usize;
+        assert_eq!(data_offset, 4096);
+        assert_eq!(buffer[4096..4096 + 16], [7; 16]);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter().eq([c"./prog"]));
+            assert!(layout.envv_iter().eq([c"FOO=bar"]));
+        }
+    }
+
+    #[test]
+    fn test_builder_build_traced() {
+        let mut builder = StackLayoutBuilder::new().with_string_pool();
+        builder.add_argv("./prog");
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::platform("x86_64"));
+        let expected = builder.clone().build();
+
+        let mut trace = String::new();
+        let buffer = builder.build_traced(&mut trace).unwrap();
+        assert_eq!(buffer.len(), expected.len());
+
+        let w = size_of::<usize>();
+        let lines = trace.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                String::from("write_argc(2) at offset 0"),
+                format!(
+                    "write_arg(\"./prog\") at entry_offset={w}, data_offset={}",
+                    12 * w + 7
+                ),
+                format!(
+                    "write_arg_ref() at entry_offset={}, data_offset={}",
+                    2 * w,
+                    12 * w + 7
+                ),
+                format!(
+                    "write_env(\"FOO=bar\") at entry_offset={}, data_offset={}",
+                    4 * w,
+                    12 * w + 14
+                ),
+                format!(
+                    "write_aux_immediate(AT_PAGESZ, 0x1000) at entry_offset={}",
+                    6 * w
+                ),
+                format!(
+                    "write_aux_refdata(AT_PLATFORM, 6 bytes) at entry_offset={}, data_offset={}",
+                    8 * w,
+                    12 * w
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_string_pool() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("/usr/lib/a");
+        builder.add_argv("/usr/lib/a");
+        builder.add_envv("FOO=bar");
+        builder.add_envv("FOO=bar");
+        builder.add_envv("BAR=foo");
+        let size = builder.total_size();
+
+        let builder = builder.with_string_pool();
+        assert_eq!(builder.total_size(), size - 11 - 8);
+        let buffer = builder.build();
+        assert_eq!(buffer.len(), size - 11 - 8);
+
+        let layout = StackLayoutRef::new(&buffer, None);
+        let argv = layout.argv_raw_entries();
+        assert_eq!(argv[0], argv[1]);
+        let envv = layout.envv_raw_entries();
+        assert_eq!(envv[0], envv[1]);
+        assert_ne!(envv[0], envv[2]);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter().eq([c"/usr/lib/a", c"/usr/lib/a"]));
+            assert!(layout.envv_iter().eq([c"FOO=bar", c"FOO=bar", c"BAR=foo"]));
+        }
+    }
+
+    #[test]
+    fn test_builder_extend() {
+        let mut builder = [AuxVar::Pagesz(4096), AuxVar::Null, AuxVar::Uid(1000)]
+            .into_iter()
+            .collect::<StackLayoutBuilder<'_>>();
+        assert_eq!(builder.auxv, [AuxVar::Pagesz(4096), AuxVar::Uid(1000)]);
+        assert!(builder.argv.is_empty());
+        assert!(builder.envv.is_empty());
+
+        builder.extend([AuxVar::Gid(100)]);
+        builder.extend_argv(["./prog", "--help"]);
+        builder.extend_envv([String::from("FOO=bar")]);
+        assert_eq!(builder.auxv.len(), 3);
+        assert_eq!(builder.argv, [b"./prog\0".to_vec(), b"--help\0".to_vec()]);
+        assert_eq!(builder.envv, [b"FOO=bar\0".to_vec()]);
+    }
+
+    #[test]
+    fn test_builder_try_add_auxv() {
+        let mut builder = StackLayoutBuilder::new();
+        assert_eq!(
+            builder.try_add_auxv(AuxVar::Null),
+            Err(AddAuxvError::ExplicitNull)
+        );
+        assert_eq!(builder.try_add_auxv(AuxVar::Pagesz(4096)), Ok(()));
+        builder.add_auxv(AuxVar::Null);
+        assert_eq!(builder.auxv, [AuxVar::Pagesz(4096)]);
+    }
+
+    #[test]
+    fn test_builder_check_auxv_plausibility() {
+        let check = StackLayoutBuilder::check_auxv_plausibility;
+        assert_eq!(check(&AuxVar::Pagesz(4096)), None);
+        assert_eq!(
+            check(&AuxVar::Pagesz(4095)),
+            Some(PlausibilityWarning::PageSizeNotPowerOfTwo(4095))
+        );
+        assert_eq!(check(&AuxVar::Phent(56)), None);
+        assert_eq!(
+            check(&AuxVar::Phent(48)),
+            Some(PlausibilityWarning::InvalidProgramHeaderSize(48))
+        );
+        assert_eq!(check(&AuxVar::Phnum(0xffff)), None);
+        assert_eq!(
+            check(&AuxVar::Phnum(0x10000)),
+            Some(PlausibilityWarning::TooManyProgramHeaders(0x10000))
+        );
+        assert_eq!(check(&AuxVar::Uid(1000)), None);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            check(&AuxVar::EGid(1 << 32)),
+            Some(PlausibilityWarning::IdExceedsU32(AuxVarType::EGid, 1 << 32))
+        );
+        assert_eq!(check(&AuxVar::Clktck(0)), None);
+
+        // Warnings don't prevent adding the entry.
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Pagesz(4095));
+        assert_eq!(builder.auxv.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "sanity_warnings")]
+    fn test_builder_take_warnings() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Pagesz(4095));
+        builder.add_auxv(AuxVar::Uid(1000));
+        builder.add_auxv(AuxVar::Phent(48));
+        assert_eq!(
+            builder.take_warnings(),
+            [
+                PlausibilityWarning::PageSizeNotPowerOfTwo(4095),
+                PlausibilityWarning::InvalidProgramHeaderSize(48)
+            ]
+        );
+        assert!(builder.take_warnings().is_empty());
+        assert_eq!(builder.auxv.len(), 3);
+    }
+
+    #[test]
+    fn test_builder_add_auxv_raw() {
+        const UNKNOWN_TYPE: usize = 0x1234;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv_raw(AuxVarRaw::new(AuxVarType::Uid, 1000));
+        builder.add_auxv_raw(AuxVarRaw::new(UNKNOWN_TYPE, 42));
+        builder.add_auxv_raw(AuxVarRaw::new(AuxVarType::Null, 0));
+        let buffer = builder.build();
+
+        let layout = StackLayoutRef::new(&buffer, None);
+        // The parser stops at the unknown type.
+        let auxv = layout.auxv_raw_iter().collect::<Vec<_>>();
+        assert_eq!(
+            auxv,
+            [
+                AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+                AuxVarRaw::new(AuxVarType::Uid, 1000)
+            ]
+        );
+
+        let words = buffer
+            .chunks_exact(size_of::<usize>())
+            .map(|word| usize::from_ne_bytes(word.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        // argc, argv NULL, envv NULL, then auxv
+        assert_eq!(words[3..], [6, 4096, 11, 1000, UNKNOWN_TYPE, 42, 0, 0]);
+    }
+
+    #[test]
+    fn test_builder_semantically_eq() {
+        let mut a = StackLayoutBuilder::new();
+        a.add_argv("./prog");
+        a.add_envv("FOO=bar");
+        a.extend([
+            AuxVar::Pagesz(4096),
+            AuxVar::Uid(1000),
+            AuxVar::platform("x86_64"),
+        ]);
+
+        let mut b = StackLayoutBuilder::new().with_string_pool();
+        b.add_argv("./prog");
+        b.add_envv("FOO=bar");
+        b.extend([
+            AuxVar::Platform(c"x86_64".into()),
+            AuxVar::Uid(1000),
+            AuxVar::Pagesz(4096),
+        ]);
+        assert_ne!(a, b);
+        assert!(a.semantically_eq(&b));
+        assert!(b.semantically_eq(&a));
+
+        // duplicates must match one by one
+        let mut c = a.clone();
+        let mut d = a.clone();
+        c.add_auxv(AuxVar::Uid(1000));
+        d.add_auxv(AuxVar::Pagesz(4096));
+        assert!(!c.semantically_eq(&d));
+
+        // the order of the arguments matters
+        let mut e = a.clone();
+        let mut f = a;
+        e.extend_argv(["-a", "-b"]);
+        f.extend_argv(["-b", "-a"]);
+        assert!(!e.semantically_eq(&f));
+    }
+
+    #[test]
+    fn test_builder_validate_for_dynamic_elf() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Entry(0x401000 as *const u8));
+        assert_eq!(
+            builder.validate_for_dynamic_elf(),
+            Err(vec![
+                AuxVarType::Phdr,
+                AuxVarType::Phent,
+                AuxVarType::Phnum,
+                AuxVarType::Base
+            ])
+        );
+
+        builder.extend([
+            AuxVar::Phdr(0x400040 as *const u8),
+            AuxVar::Phent(56),
+            AuxVar::Phnum(9),
+            AuxVar::Base(0x7f00_0000 as *const u8),
+        ]);
+        assert_eq!(builder.validate_for_dynamic_elf(), Ok(()));
+    }
+
+    #[test]
+    fn test_builder_check_auxv_consistency() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn(c"./prog".into()));
+        builder.add_auxv(AuxVar::Platform("x86_64".into()));
+        assert_eq!(builder.check_auxv_consistency(), []);
+
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Pagesz(65536));
+        builder.override_argc(2);
+        assert_eq!(
+            builder.check_auxv_consistency(),
+            [
+                ConsistencyWarning::DuplicateType(AuxVarType::Pagesz),
+                ConsistencyWarning::ArgcMismatch
+            ]
+        );
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::ExecFn(c"x86_64".into()));
+        builder.add_auxv(AuxVar::Platform("x86_64".into()));
+        assert_eq!(
+            builder.check_auxv_consistency(),
+            [ConsistencyWarning::ExecFnEqualsPlatform]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", unix))]
+    #[cfg_attr(miri, ignore = "spawning processes is not supported by miri")]
+    fn test_builder_into_child_stdin() {
+        use std::process::{Command, Stdio};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        let expected_len = builder.total_size();
+
+        let mut child = Command::new("wc")
+            .arg("-c")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        assert_eq!(
+            builder.clone().into_child_stdin(&mut child).unwrap(),
+            expected_len
+        );
+        let output = child.wait_with_output().unwrap();
+        let count = std::str::from_utf8(&output.stdout).unwrap().trim();
+        assert_eq!(count, std::format!("{expected_len}"));
+
+        let mut child = Command::new("true").stdin(Stdio::null()).spawn().unwrap();
+        let err = builder.into_child_stdin(&mut child).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_builder_data_len_so_far() {
+        let mut builder = StackLayoutBuilder::new();
+        assert_eq!(builder.argv_data_len_so_far(), 0);
+        builder.add_argv("./prog");
+        builder.add_argv("--help");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        builder.add_auxv(AuxVar::Random([0; 16]));
+        assert_eq!(builder.argv_data_len_so_far(), 7 + 7);
+        assert_eq!(builder.envv_data_len_so_far(), 8);
+        assert_eq!(builder.auxv_data_len_so_far(), 7 + 16);
+        let data_len = builder.argv_data_len_so_far()
+            + builder.envv_data_len_so_far()
+            + builder.auxv_data_len_so_far();
+        assert_eq!(
+            builder.total_size() - data_len,
+            (1 + 3 + 2 + 8) * size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_builder_stack_alignment() {
+        #[repr(C, align(64))]
+        struct Stack([u8; 512]);
+
+        let mut builder = StackLayoutBuilder::new().with_stack_alignment(64);
+        builder.add_argv("./prog");
+        let mut stack = Stack([0; 512]);
+        let (offset, len) = builder.build_on_slice(&mut stack.0);
+        assert_eq!((stack.0.as_ptr() as usize + offset) % 64, 0);
+        assert!(stack.0.len() - (offset + len) < 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_builder_stack_alignment_invalid() {
+        let _ = StackLayoutBuilder::new().with_stack_alignment(24);
+    }
+
+    #[test]
+    fn test_builder_on_slice() {
+        #[repr(C, align(16))]
+        struct Stack([u8; 512]);
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn(c"./prog".into()));
+        let expected_len = builder.total_size();
+
+        let mut stack = Stack([0xff; 512]);
+        let (offset, len) = builder.build_on_slice(&mut stack.0);
+        assert_eq!(len, expected_len);
+        assert_eq!(offset % (2 * align_of::<usize>()), 0);
+        assert!(offset + len <= stack.0.len());
+        assert!(stack.0.len() - (offset + len) < 2 * align_of::<usize>());
+
+        let layout = StackLayoutRef::new(&stack.0[offset..offset + len], None);
+        assert_eq!(layout.argc(), 1);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter().eq([c"./prog"]));
+            assert!(layout.envv_iter().eq([c"FOO=bar"]));
+        }
+        assert_eq!(
+            layout.nth_auxv_raw(0),
+            Some(AuxVarRaw::new(AuxVarType::Pagesz, 4096))
+        );
+        assert_eq!(layout.auxvc(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "stack memory should be large enough for the layout")]
+    fn test_builder_on_slice_too_small() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        let _ = builder.build_on_slice(&mut [0; 8]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_builder_to_exec_args() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--help\0");
+        builder.add_envv("FOO=bar");
+
+        let (argv, envv) = builder.to_exec_args();
+        assert_eq!(argv, [CString::from(c"./prog"), CString::from(c"--help")]);
+        assert_eq!(envv, [CString::from(c"FOO=bar")]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", unix))]
+    fn test_builder_os_str() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut builder = StackLayoutBuilder::new();
+        assert_eq!(
+            builder.add_argv_os(OsStr::from_bytes(b"/tmp/\xff\xfe")),
+            Ok(())
+        );
+        assert_eq!(builder.add_envv_os(OsStr::from_bytes(b"KEY=\xff")), Ok(()));
+        assert_eq!(
+            builder.add_argv_os(OsStr::from_bytes(b"a\0b")),
+            Err(OsStrError::InvalidEncoding)
+        );
+        assert_eq!(
+            builder.add_envv_os(OsStr::from_bytes(b"KEY=a\0b")),
+            Err(OsStrError::InvalidEncoding)
+        );
+        builder.set_envv_key_policy(EnvKeyPolicy::PosixStrict);
+        assert_eq!(
+            builder.add_envv_os(OsStr::from_bytes(b"\xff=value")),
+            Err(OsStrError::InvalidKey)
+        );
+        assert_eq!(builder.program_name(), None);
+
+        let layout = builder.build();
+        let layout = StackLayoutRef::new(layout.as_ref(), None);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(
+                layout
+                    .argv_iter()
+                    .map(CStr::to_bytes)
+                    .eq([b"/tmp/\xff\xfe".as_slice()])
+            );
+            assert!(
+                layout
+                    .envv_iter()
+                    .map(CStr::to_bytes)
+                    .eq([b"KEY=\xff".as_slice()])
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_linux_limits() {
+        let too_long = "a".repeat(MAX_ARG_STRLEN);
+        let max_len = "a".repeat(MAX_ARG_STRLEN - 1 /* NUL */);
+
+        let mut builder = StackLayoutBuilder::new();
+        assert_eq!(builder.try_add_argv(too_long.as_str()), Ok(()));
+
+        builder.enforce_linux_limits(true);
+        assert_eq!(builder.try_add_argv(max_len.as_str()), Ok(()));
+        assert_eq!(
+            builder.try_add_argv(too_long.as_str()),
+            Err(LimitError::StringTooLong)
+        );
+
+        let env = format!("FOO={}", &too_long[4..]);
+        assert_eq!(
+            builder.try_add_envv(env.as_str()),
+            Err(EnvError::Limit(LimitError::StringTooLong))
+        );
+        assert_eq!(builder.try_add_envv(&env[1..]), Ok(()));
+        assert_eq!(builder.argv.len(), 2);
+        assert_eq!(builder.envv.len(), 1);
+    }
 }
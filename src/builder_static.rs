@@ -0,0 +1,332 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::serializer::StackLayoutSerializer;
+use crate::util::get_null_index;
+use crate::{AuxVar, AuxVarRaw, AuxVarType};
+use core::ffi::CStr;
+
+/// Error that [`StackLayoutBuilderStatic`] reports if one of its buffers or
+/// the provided output buffer is too small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("capacity of the static buffer is exhausted")]
+pub struct CapacityError;
+
+/// Like [`StackLayoutBuilder`] but without any heap allocations. This is
+/// useful for `no_std` environments without `alloc`, such as bare-metal
+/// loaders.
+///
+/// The strings of `argv` and `envv`, the `auxv` entries, and the `auxv`
+/// payloads are each stored in a dedicated byte array of `N` bytes. Once an
+/// array is full, a [`CapacityError`] is reported.
+///
+/// [`StackLayoutBuilder`]: crate::StackLayoutBuilder
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackLayoutBuilderStatic<const N: usize> {
+    // NUL-terminated strings, back-to-back.
+    argv_data: [u8; N],
+    argv_data_len: usize,
+    argc: usize,
+    // NUL-terminated strings, back-to-back.
+    envv_data: [u8; N],
+    envv_data_len: usize,
+    envc: usize,
+    // Serialized `AuxVarRaw` entries. For entries with a payload in the auxv
+    // data area, the value is the offset into `auxv_data`.
+    auxv: [u8; N],
+    auxvc: usize,
+    auxv_data: [u8; N],
+    auxv_data_len: usize,
+}
+
+impl<const N: usize> StackLayoutBuilderStatic<N> {
+    /// Creates a new builder.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            argv_data: [0; N],
+            argv_data_len: 0,
+            argc: 0,
+            envv_data: [0; N],
+            envv_data_len: 0,
+            envc: 0,
+            auxv: [0; N],
+            auxvc: 0,
+            auxv_data: [0; N],
+            auxv_data_len: 0,
+        }
+    }
+
+    /// Appends the string plus a NUL byte to `data`.
+    ///
+    /// Adding a terminating NUL byte is not necessary. Interim NUL bytes are
+    /// prohibited.
+    fn push_cstr(data: &mut [u8; N], len: &mut usize, str: &[u8]) -> Result<(), CapacityError> {
+        if let Some(pos) = get_null_index(str) {
+            assert_eq!(
+                pos,
+                str.len() - 1,
+                "strings must not contain interim NUL bytes"
+            );
+        }
+        let str = str.strip_suffix(&[0]).unwrap_or(str);
+
+        let end = *len + str.len() + 1 /* NUL */;
+        if end > N {
+            return Err(CapacityError);
+        }
+        data[*len..end - 1].copy_from_slice(str);
+        data[end - 1] = 0;
+        *len = end;
+        Ok(())
+    }
+
+    /// Adds an argument to the builder.
+    ///
+    /// Adding a terminating NUL byte is not necessary. Interim NUL bytes are
+    /// prohibited.
+    pub fn add_argv(&mut self, arg: &str) -> Result<(), CapacityError> {
+        Self::push_cstr(&mut self.argv_data, &mut self.argv_data_len, arg.as_bytes())?;
+        self.argc += 1;
+        Ok(())
+    }
+
+    /// Adds an environment-variable to the builder.
+    ///
+    /// Adding a terminating NUL byte is not necessary. Interim NUL bytes are
+    /// prohibited.
+    ///
+    /// The value must follow the `key=value` syntax, where `value` may be
+    /// empty.
+    pub fn add_envv(&mut self, env: &str) -> Result<(), CapacityError> {
+        // Check syntax
+        {
+            let (key, _value) = env
+                .split_once('=')
+                .expect("should have ENV var syntax (`key=value`)");
+            assert!(!key.is_empty());
+        }
+
+        Self::push_cstr(&mut self.envv_data, &mut self.envv_data_len, env.as_bytes())?;
+        self.envc += 1;
+        Ok(())
+    }
+
+    /// Adds an [`AuxVar`] to the builder.
+    pub fn add_auxv(&mut self, aux: AuxVar<'_>) -> Result<(), CapacityError> {
+        // Ignore, we do this automatically in the end.
+        if aux == AuxVar::Null {
+            return Ok(());
+        }
+
+        let entry_begin = self.auxvc * size_of::<AuxVarRaw>();
+        let entry_end = entry_begin + size_of::<AuxVarRaw>();
+        if entry_end > N {
+            return Err(CapacityError);
+        }
+
+        let value = match &aux {
+            AuxVar::Platform(v) | AuxVar::BasePlatform(v) | AuxVar::ExecFn(v) => {
+                let offset = self.auxv_data_len;
+                Self::push_cstr(&mut self.auxv_data, &mut self.auxv_data_len, v.as_bytes())?;
+                offset
+            }
+            AuxVar::Random(v) => {
+                let offset = self.auxv_data_len;
+                let end = offset + v.len();
+                if end > N {
+                    return Err(CapacityError);
+                }
+                self.auxv_data[offset..end].copy_from_slice(v);
+                self.auxv_data_len = end;
+                offset
+            }
+            _ => aux.value_raw(),
+        };
+
+        let entry = AuxVarRaw::new(aux.key(), value);
+        self.auxv[entry_begin..entry_end].copy_from_slice(entry.as_bytes());
+        self.auxvc += 1;
+        Ok(())
+    }
+
+    /// Returns the total size in bytes needed for the structure.
+    ///
+    /// This includes any null entries or padding.
+    #[must_use]
+    pub const fn total_size(&self) -> usize {
+        size_of::<usize>() /* argc */
+            + self.len_argv_entries()
+            + self.len_envv_entries()
+            + self.len_auxv_entries()
+            + self.argv_data_len
+            + self.envv_data_len
+            + self.auxv_data_len
+    }
+
+    /// Returns the size in bytes needed for the `argv` entries.
+    ///
+    /// This includes the terminating null entry.
+    const fn len_argv_entries(&self) -> usize {
+        (self.argc + 1/* null */) * size_of::<usize>()
+    }
+
+    /// Returns the size in bytes needed for the `envv` entries.
+    ///
+    /// This includes the terminating null entry.
+    const fn len_envv_entries(&self) -> usize {
+        (self.envc + 1/* null */) * size_of::<usize>()
+    }
+
+    /// Returns the size in bytes needed for the `auxv` entries.
+    ///
+    /// This includes the terminating null entry.
+    const fn len_auxv_entries(&self) -> usize {
+        (self.auxvc + 1/* NULL entry */) * size_of::<AuxVarRaw>()
+    }
+
+    /// Builds the layout into the beginning of `buffer`.
+    ///
+    /// The buffer must be aligned to `align_of::<usize>()`. All pointers refer
+    /// to the address of `buffer`. On success, the number of used bytes, i.e.,
+    /// [`Self::total_size`], is returned.
+    pub fn build_into_slice(&self, buffer: &mut [u8]) -> Result<usize, CapacityError> {
+        let len = self.total_size();
+        let buffer = buffer.get_mut(..len).ok_or(CapacityError)?;
+        // Zeroed buffer. Enables us to not write dedicated NULL entries into
+        // `argv` and `envv`.
+        buffer.fill(0);
+
+        let mut serializer = StackLayoutSerializer::new(
+            buffer,
+            size_of::<usize>(),
+            self.len_argv_entries(),
+            self.len_envv_entries(),
+            self.len_auxv_entries(),
+            0,
+            self.argv_data_len,
+            self.envv_data_len,
+            self.auxv_data_len,
+        );
+        serializer.write_argc(self.argc);
+
+        for arg in self.argv_data[..self.argv_data_len].split_inclusive(|&b| b == 0) {
+            serializer.write_arg(CStr::from_bytes_with_nul(arg).unwrap());
+        }
+        // Writing NULL entry not necessary, the buffer is already zeroed
+
+        for var in self.envv_data[..self.envv_data_len].split_inclusive(|&b| b == 0) {
+            serializer.write_env(CStr::from_bytes_with_nul(var).unwrap());
+        }
+        // Writing NULL entry not necessary, the buffer is already zeroed
+
+        for entry in self.auxv[..self.auxvc * size_of::<AuxVarRaw>()].chunks(size_of::<AuxVarRaw>())
+        {
+            let entry = AuxVarRaw::from_bytes(entry).unwrap();
+            let key = entry.key().unwrap();
+            if key.value_in_data_area() {
+                let data = &self.auxv_data[entry.value()..self.auxv_data_len];
+                match key.data_area_val_size_hint() {
+                    Some(size) => serializer.write_aux_refdata(key, &data[..size], false),
+                    None => {
+                        let cstr = CStr::from_bytes_until_nul(data).unwrap();
+                        serializer.write_aux_refdata(key, cstr.to_bytes_with_nul(), true);
+                    }
+                }
+            } else {
+                serializer.write_aux_immediate(key, entry.value());
+            }
+        }
+        serializer.write_aux_immediate(AuxVarType::Null, 0);
+
+        Ok(len)
+    }
+}
+
+impl<const N: usize> Default for StackLayoutBuilderStatic<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StackLayoutRef;
+
+    #[repr(C, align(16))]
+    struct Aligned16<T>(T);
+
+    #[test]
+    fn test_builder_static() {
+        let mut builder = StackLayoutBuilderStatic::<128>::new();
+        builder.add_argv("first arg").unwrap();
+        builder.add_argv("second arg\0").unwrap();
+        builder.add_envv("var1=foo").unwrap();
+        builder.add_auxv(AuxVar::Uid(1000)).unwrap();
+        builder
+            .add_auxv(AuxVar::Random([
+                0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+            ]))
+            .unwrap();
+        builder
+            .add_auxv(AuxVar::ExecFn(c"/usr/bin/foo".into()))
+            .unwrap();
+        builder.add_auxv(AuxVar::Platform("x86_64".into())).unwrap();
+
+        let mut buffer = Aligned16([0_u8; 512]);
+        assert_eq!(
+            builder.build_into_slice(&mut buffer.0[..8]),
+            Err(CapacityError)
+        );
+        let len = builder.build_into_slice(&mut buffer.0).unwrap();
+        assert_eq!(len, builder.total_size());
+
+        let layout = StackLayoutRef::new(&buffer.0[..len], None);
+        assert_eq!(layout.argc(), 2);
+        assert_eq!(layout.envc(), 1);
+        assert_eq!(layout.auxvc(), 4);
+
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter().eq([c"first arg", c"second arg"]));
+            assert!(layout.envv_iter().eq([c"var1=foo"]));
+            assert!(layout.auxv_iter().eq([
+                AuxVar::Uid(1000),
+                AuxVar::Random([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]),
+                AuxVar::ExecFn(c"/usr/bin/foo".into()),
+                AuxVar::Platform(c"x86_64".into()),
+            ]));
+        }
+    }
+
+    #[test]
+    fn test_builder_static_capacity() {
+        let mut builder = StackLayoutBuilderStatic::<8>::new();
+        builder.add_argv("1234567").unwrap();
+        assert_eq!(builder.add_argv("1"), Err(CapacityError));
+        builder.add_envv("A=3456").unwrap();
+        assert_eq!(builder.add_envv("B="), Err(CapacityError));
+        assert_eq!(builder.add_auxv(AuxVar::Uid(1000)), Err(CapacityError));
+    }
+}
@@ -0,0 +1,220 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Owned, pointer-free representation of a stack layout.
+
+use crate::aux_var::AuxVarString;
+use crate::{AuxVar, AuxVarType, StackLayoutRef};
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "builder")]
+use {crate::AuxVarRaw, crate::StackLayoutBuilder, alloc::ffi::CString};
+
+/// The value of an entry of the auxiliary vector in a [`CompactLayout`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AuxVarValue {
+    /// A value that is stored in the entry itself.
+    Immediate(usize),
+    /// Bytes referenced by the entry, i.e., the payload of
+    /// [`AuxVarType::Random`] or a string that is not valid UTF-8.
+    Bytes(Vec<u8>),
+    /// A string referenced by the entry, without the NUL byte.
+    String(String),
+}
+
+/// Owned representation of the semantically meaningful content of a stack
+/// layout, created by [`StackLayoutRef::compact_representation`].
+///
+/// Unlike the layout itself, it contains no pointers, so it can be stored and
+/// compared without any lifetime or safety concerns. Strings are stored
+/// without their NUL bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CompactLayout {
+    /// The value of `argc`.
+    pub argc: usize,
+    /// The arguments.
+    pub argv: Vec<Vec<u8>>,
+    /// The environment variables.
+    pub envv: Vec<Vec<u8>>,
+    /// The entries of the auxiliary vector, without the terminating
+    /// [`AuxVarType::Null`] entry.
+    pub auxv: Vec<(AuxVarType, AuxVarValue)>,
+}
+
+impl StackLayoutRef<'_> {
+    /// Copies the content of the layout into an owned [`CompactLayout`].
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn compact_representation(&self) -> CompactLayout {
+        let to_vec = |str: &core::ffi::CStr| str.to_bytes().to_vec();
+        let auxv = unsafe { self.auxv_iter() }
+            .map(|aux| {
+                let value = match &aux {
+                    AuxVar::Platform(str) | AuxVar::BasePlatform(str) | AuxVar::ExecFn(str) => {
+                        string_value(str)
+                    }
+                    AuxVar::Random(bytes) => AuxVarValue::Bytes(bytes.to_vec()),
+                    aux => AuxVarValue::Immediate(aux.value_raw()),
+                };
+                (aux.key(), value)
+            })
+            .collect();
+        CompactLayout {
+            argc: self.argc(),
+            argv: unsafe { self.argv_iter() }.map(to_vec).collect(),
+            envv: unsafe { self.envv_iter() }.map(to_vec).collect(),
+            auxv,
+        }
+    }
+}
+
+/// Converts a string payload into an [`AuxVarValue::String`], or into
+/// [`AuxVarValue::Bytes`] if it is not valid UTF-8.
+fn string_value(str: &AuxVarString<'_>) -> AuxVarValue {
+    let bytes = &str.as_bytes()[..str.count_bytes()];
+    core::str::from_utf8(bytes).map_or_else(
+        |_| AuxVarValue::Bytes(bytes.to_vec()),
+        |str| AuxVarValue::String(str.into()),
+    )
+}
+
+#[cfg(feature = "builder")]
+impl StackLayoutBuilder<'static> {
+    /// Creates a builder from a [`CompactLayout`], which is the reverse of
+    /// [`StackLayoutRef::compact_representation`].
+    ///
+    /// If `argc` doesn't match the number of arguments, it is set by
+    /// [`Self::override_argc`].
+    ///
+    /// # Panics
+    /// Panics if a string contains interim NUL bytes, if an environment
+    /// variable doesn't follow the `key=value` syntax, or if an
+    /// [`AuxVarValue`] doesn't fit the [`AuxVarType`], e.g., a
+    /// [`AuxVarValue::String`] for [`AuxVarType::Pagesz`].
+    #[must_use]
+    pub fn from_compact(compact: &CompactLayout) -> Self {
+        let mut builder = Self::new();
+        for arg in &compact.argv {
+            let arg =
+                Self::try_nul_terminate(arg.clone()).expect("should have no interim NUL bytes");
+            builder
+                .push_argv(arg)
+                .expect("limits should not be enforced");
+        }
+        for env in &compact.envv {
+            let env =
+                Self::try_nul_terminate(env.clone()).expect("should have no interim NUL bytes");
+            builder
+                .push_envv(env)
+                .expect("keys and limits should not be checked");
+        }
+        if compact.argc != compact.argv.len() {
+            builder.override_argc(compact.argc);
+        }
+        for (key, value) in &compact.auxv {
+            builder.add_auxv(aux_var_from_value(*key, value));
+        }
+        builder
+    }
+}
+
+/// Creates the [`AuxVar`] of type `key` with the given `value`.
+///
+/// # Panics
+/// Panics if the value doesn't fit the type.
+#[cfg(feature = "builder")]
+fn aux_var_from_value(key: AuxVarType, value: &AuxVarValue) -> AuxVar<'static> {
+    let string = || -> AuxVarString<'static> {
+        match value {
+            AuxVarValue::String(str) => str.clone().into(),
+            AuxVarValue::Bytes(bytes) => CString::new(bytes.clone())
+                .expect("should have no interim NUL bytes")
+                .into(),
+            AuxVarValue::Immediate(_) => panic!("{key:?} should have a string value"),
+        }
+    };
+    match (key, value) {
+        (AuxVarType::Platform, _) => AuxVar::Platform(string()),
+        (AuxVarType::BasePlatform, _) => AuxVar::BasePlatform(string()),
+        (AuxVarType::ExecFn, _) => AuxVar::ExecFn(string()),
+        (AuxVarType::Random, AuxVarValue::Bytes(bytes)) => AuxVar::Random(
+            bytes
+                .as_slice()
+                .try_into()
+                .expect("AT_RANDOM should have 16 bytes"),
+        ),
+        (key, AuxVarValue::Immediate(value)) if !key.value_in_data_area() => {
+            // SAFETY: The value of the remaining types is not in the data
+            // area, so the buffer is never accessed.
+            unsafe { AuxVar::from_raw(&AuxVarRaw::new(key, *value), &[]) }
+        }
+        (key, value) => panic!("{value:?} should fit {key:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_compact_round_trip() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.extend_argv(["./prog", "--flag"]);
+        builder.add_envv("FOO=bar");
+        builder.extend([
+            AuxVar::Pagesz(4096),
+            AuxVar::Random([7; 16]),
+            AuxVar::platform("x86_64"),
+        ]);
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        // SAFETY: This was created for the address space of this process.
+        let compact = unsafe { layout.compact_representation() };
+        assert_eq!(
+            compact,
+            CompactLayout {
+                argc: 2,
+                argv: vec![b"./prog".to_vec(), b"--flag".to_vec()],
+                envv: vec![b"FOO=bar".to_vec()],
+                auxv: vec![
+                    (AuxVarType::Pagesz, AuxVarValue::Immediate(4096)),
+                    (AuxVarType::Random, AuxVarValue::Bytes(vec![7; 16])),
+                    (AuxVarType::Platform, AuxVarValue::String("x86_64".into())),
+                ],
+            }
+        );
+
+        let buffer = StackLayoutBuilder::from_compact(&compact).build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        // SAFETY: This was created for the address space of this process.
+        assert_eq!(unsafe { layout.compact_representation() }, compact);
+    }
+}
@@ -152,21 +152,113 @@ SOFTWARE.
 #![deny(rustdoc::all)]
 #![no_std]
 
-#[cfg_attr(feature = "alloc", macro_use)]
+#[cfg_attr(all(feature = "alloc", not(test)), macro_use)]
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
 #[cfg_attr(test, macro_use)]
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
-pub use aux_var::{AuxVar, AuxVarFlags, AuxVarRaw, AuxVarType};
+pub use arch::Arch;
+pub use aux_var::{AtFlags, AuxVar, AuxVarFlags, AuxVarRaw, AuxVarType};
+#[cfg(all(feature = "builder", feature = "std", unix))]
+pub use builder::OsStrError;
 #[cfg(feature = "builder")]
-pub use builder::StackLayoutBuilder;
-pub use parser::StackLayoutRef;
+pub use builder::{
+    AddAuxvError, ConsistencyWarning, DataMap, EnvError, EnvKeyPolicy, LimitError,
+    PlausibilityWarning, StackLayoutBuilder,
+};
+pub use builder_static::{CapacityError, StackLayoutBuilderStatic};
+#[cfg(feature = "alloc")]
+pub use compact::{AuxVarValue, CompactLayout};
+pub use local::LocalStackLayout;
+#[cfg(all(feature = "std", unix))]
+pub use mmap::MmapLayoutRef;
+pub use offset::OffsetStackLayoutRef;
+#[cfg(feature = "alloc")]
+pub use parser::LayoutDiff;
+#[cfg(feature = "std")]
+pub use parser::ReadError;
+pub use parser::{
+    AlignmentError, ArgvView, AuxvCastError, AuxvIter, AuxvSection, EnvvAuxvView, LayoutEntry,
+    LayoutFormat, LayoutSection, PatchError, ProcessInfo, SectionKind, StackLayoutError,
+    StackLayoutRef, StringKind,
+};
+pub use parser32::StackLayoutRef32;
+pub use parser64::StackLayoutRef64;
+#[cfg(feature = "builder")]
+pub use spec::{ParseError, ParseErrorKind};
 
+mod arch;
 mod aux_var;
 #[cfg(feature = "builder")]
 mod builder;
+mod builder_static;
+#[cfg(feature = "alloc")]
+mod compact;
+mod local;
+#[cfg(all(feature = "std", unix))]
+mod mmap;
+mod offset;
 mod parser;
+mod parser32;
+mod parser64;
+mod serializer;
+#[cfg(feature = "builder")]
+mod spec;
 mod util;
+
+/// Maximum length in bytes of a single argument or environment variable,
+/// including the terminating NUL byte, that Linux accepts.
+///
+/// See <https://elixir.bootlin.com/linux/v6.15/source/include/uapi/linux/binfmts.h#L15>.
+pub const MAX_ARG_STRLEN: usize = 4096 * 32;
+
+/// Maximum number of arguments respectively environment variables that Linux
+/// accepts.
+///
+/// See <https://elixir.bootlin.com/linux/v6.15/source/include/uapi/linux/binfmts.h#L16>.
+pub const MAX_ARG_STRINGS: usize = 0x7FFF_FFFF;
+
+/// Size in bytes of a word of the host, i.e., of `argc` and of each entry of
+/// `argv` and `envv`.
+pub const WORD_SIZE: usize = size_of::<usize>();
+
+/// Size in bytes of an entry of the auxiliary vector (`auxv`) of the host,
+/// i.e., of an [`AuxVarRaw`].
+pub const AUXVAR_RAW_SIZE: usize = size_of::<AuxVarRaw>();
+
+/// Order in which Linux emits the entries of the auxiliary vector (`auxv`).
+///
+/// The architecture-specific entries (`ARCH_DLINFO`) come first, followed by
+/// the generic entries. Types that are not part of this list are not emitted
+/// by the ELF loader of Linux.
+///
+/// See `create_elf_tables()` in
+/// <https://elixir.bootlin.com/linux/v6.15/source/fs/binfmt_elf.c>.
+pub const KERNEL_AUXV_ORDER: &[AuxVarType] = &[
+    AuxVarType::Sysinfo,
+    AuxVarType::SysinfoEhdr,
+    AuxVarType::MinSigStkSz,
+    AuxVarType::HwCap,
+    AuxVarType::Pagesz,
+    AuxVarType::Clktck,
+    AuxVarType::Phdr,
+    AuxVarType::Phent,
+    AuxVarType::Phnum,
+    AuxVarType::Base,
+    AuxVarType::Flags,
+    AuxVarType::Entry,
+    AuxVarType::Uid,
+    AuxVarType::EUid,
+    AuxVarType::Gid,
+    AuxVarType::EGid,
+    AuxVarType::Secure,
+    AuxVarType::Random,
+    AuxVarType::HwCap2,
+    AuxVarType::ExecFn,
+    AuxVarType::Platform,
+    AuxVarType::BasePlatform,
+    AuxVarType::ExecFd,
+];
@@ -0,0 +1,167 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{AuxVar, StackLayoutRef};
+use core::ffi::CStr;
+
+/// A [`StackLayoutRef`] whose pointers all reference data within its
+/// underlying buffer.
+///
+/// This is the case for stack layouts built for the current address space,
+/// for example, by the [`StackLayoutBuilder`]. As all pointer dereferences are
+/// known to be valid, this type offers safe iterators.
+///
+/// [`StackLayoutBuilder`]: crate::StackLayoutBuilder
+#[derive(Debug)]
+pub struct LocalStackLayout<'a> {
+    layout: StackLayoutRef<'a>,
+}
+
+impl<'a> LocalStackLayout<'a> {
+    /// Creates a new view into the stack layout in `bytes`, which start with
+    /// `argc`.
+    ///
+    /// Returns `None` if the layout is malformed or if any pointer references
+    /// memory outside `bytes`. See [`StackLayoutRef::into_local`].
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Option<Self> {
+        StackLayoutRef::try_new(bytes, None).ok()?.into_local()
+    }
+
+    /// Wraps a layout that is known to only contain local pointers.
+    pub(crate) const fn new_unchecked(layout: StackLayoutRef<'a>) -> Self {
+        Self { layout }
+    }
+
+    /// Returns the underlying [`StackLayoutRef`].
+    #[must_use]
+    pub const fn as_layout_ref(&self) -> &StackLayoutRef<'a> {
+        &self.layout
+    }
+
+    /// Returns the number of arguments.
+    #[must_use]
+    pub fn argc(&self) -> usize {
+        self.layout.argc()
+    }
+
+    /// Returns the number of environment variables.
+    #[must_use]
+    pub fn envc(&self) -> usize {
+        self.layout.envc()
+    }
+
+    /// Returns the number of auxiliary vector entries.
+    #[must_use]
+    pub fn auxvc(&self) -> usize {
+        self.layout.auxvc()
+    }
+
+    /// Safe version of [`StackLayoutRef::argv_iter`].
+    #[must_use]
+    pub fn argv_iter(&self) -> impl ExactSizeIterator<Item = &'a CStr> {
+        // SAFETY: All pointers were checked to be within the buffer.
+        unsafe { self.layout.argv_iter() }
+    }
+
+    /// Safe version of [`StackLayoutRef::envv_iter`].
+    #[must_use]
+    pub fn envv_iter(&self) -> impl ExactSizeIterator<Item = &'a CStr> {
+        // SAFETY: All pointers were checked to be within the buffer.
+        unsafe { self.layout.envv_iter() }
+    }
+
+    /// Safe version of [`StackLayoutRef::auxv_iter`].
+    #[must_use]
+    pub fn auxv_iter(&self) -> impl ExactSizeIterator<Item = AuxVar<'a>> {
+        // SAFETY: All pointers were checked to be within the buffer.
+        unsafe { self.layout.auxv_iter() }
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use super::*;
+    use crate::StackLayoutBuilder;
+    use std::vec::Vec;
+
+    fn builder() -> StackLayoutBuilder<'static> {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Random([1; 16]));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        builder
+    }
+
+    #[test]
+    fn test_local_stack_layout() {
+        let buffer = builder().build();
+        let layout = LocalStackLayout::new(buffer.as_ref()).unwrap();
+        assert_eq!(layout.argc(), 1);
+        assert_eq!(layout.envc(), 1);
+        assert_eq!(layout.auxvc(), 3);
+        assert_eq!(layout.argv_iter().collect::<Vec<_>>(), [c"./prog"]);
+        assert_eq!(layout.envv_iter().collect::<Vec<_>>(), [c"FOO=bar"]);
+        assert_eq!(
+            layout.auxv_iter().collect::<Vec<_>>(),
+            [
+                AuxVar::Pagesz(4096),
+                AuxVar::Random([1; 16]),
+                AuxVar::ExecFn(c"./prog".into())
+            ]
+        );
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        assert!(layout.into_local().is_some());
+    }
+
+    #[test]
+    fn test_local_stack_layout_random_at_end() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Random([1; 16]));
+        let buffer = builder.build();
+        // The random bytes are the last bytes of the buffer.
+        assert_eq!(buffer[buffer.len() - 16..], [1; 16]);
+        let layout = LocalStackLayout::new(buffer.as_ref()).unwrap();
+        assert!(layout.auxv_iter().eq([AuxVar::Random([1; 16])]));
+    }
+
+    #[test]
+    fn test_local_stack_layout_remote_pointers() {
+        let buffer = builder().build_at_address(0x1000);
+        assert!(LocalStackLayout::new(buffer.as_ref()).is_none());
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        assert!(layout.into_local().is_none());
+
+        // Remote pointers are only a problem if they are used for data
+        // references.
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Entry(0x1000 as _));
+        let buffer = builder.build_at_address(0x1000);
+        assert!(LocalStackLayout::new(buffer.as_ref()).is_some());
+    }
+}
@@ -0,0 +1,191 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::StackLayoutRef;
+use core::ffi::{c_int, c_void};
+use core::ptr::NonNull;
+use std::fs::File;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+/// The type of file offsets, i.e., `off_t` of the C library.
+///
+/// On 32-bit musl, `off_t` is always 64-bit, whereas it matches `long`
+/// otherwise.
+#[cfg(all(target_env = "musl", target_pointer_width = "32"))]
+#[allow(non_camel_case_types)]
+type off_t = i64;
+#[cfg(not(all(target_env = "musl", target_pointer_width = "32")))]
+#[allow(non_camel_case_types)]
+type off_t = core::ffi::c_long;
+
+const PROT_READ: c_int = 1;
+const MAP_PRIVATE: c_int = 2;
+
+unsafe extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: off_t,
+    ) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+/// A [`StackLayoutRef`] backed by a read-only memory mapping of a file.
+///
+/// This enables zero-copy reading of a stack layout that was saved to a
+/// file, for example, the buffer returned by [`StackLayoutBuilder::build`].
+/// Created by [`StackLayoutRef::from_mmap_file`].
+///
+/// As the pointers in the layout belong to the address space the layout was
+/// created for, typically only the raw values can be inspected.
+///
+/// The layout is accessed via [`Self::as_layout_ref`] rather than [`Deref`],
+/// as the target type would have to be `StackLayoutRef<'static>`, which would
+/// let borrows of the mapping outlive it.
+///
+/// [`Deref`]: core::ops::Deref
+/// [`StackLayoutBuilder::build`]: crate::StackLayoutBuilder::build
+#[derive(Debug)]
+pub struct MmapLayoutRef {
+    addr: NonNull<c_void>,
+    len: usize,
+    // Borrows the mapping, which lives as long as `self`.
+    layout: StackLayoutRef<'static>,
+}
+
+impl MmapLayoutRef {
+    /// Maps the file at `path` and validates the layout in it.
+    ///
+    /// # Safety
+    /// The file must not be modified while the mapping is alive.
+    pub(crate) unsafe fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "file is empty"));
+        }
+
+        // SAFETY: We map a file that we opened for reading. The `MAP_PRIVATE`
+        // mapping is never written and the caller guarantees that the file
+        // isn't modified.
+        let addr = unsafe {
+            mmap(
+                core::ptr::null_mut(),
+                len,
+                PROT_READ,
+                MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        // `MAP_FAILED`
+        if addr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let addr = NonNull::new(addr).expect("mmap should not return NULL");
+
+        // SAFETY: The mapping is valid for reads of `len` bytes until it is
+        // unmapped on drop. We only hand out borrows bound to `self`.
+        let bytes = unsafe { core::slice::from_raw_parts(addr.as_ptr().cast::<u8>(), len) };
+        let layout = match StackLayoutRef::try_new(bytes, None) {
+            Ok(layout) => layout,
+            Err(e) => {
+                // SAFETY: The mapping is not referenced anymore.
+                unsafe { munmap(addr.as_ptr(), len) };
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+        };
+
+        Ok(Self { addr, len, layout })
+    }
+
+    /// Returns the underlying [`StackLayoutRef`], which borrows the mapping.
+    #[must_use]
+    pub const fn as_layout_ref(&self) -> &StackLayoutRef<'_> {
+        &self.layout
+    }
+}
+
+impl Drop for MmapLayoutRef {
+    fn drop(&mut self) {
+        // SAFETY: All borrows of the mapping are bound to `self`.
+        unsafe { munmap(self.addr.as_ptr(), self.len) };
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use crate::{AuxVar, StackLayoutBuilder, StackLayoutRef};
+    use std::vec::Vec;
+
+    #[test]
+    #[cfg_attr(miri, ignore = "mmap is not supported by miri")]
+    fn test_from_mmap_file() {
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("foo");
+        builder.add_envv("PATH=/bin");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Platform("x86_64".into()));
+        let buffer = builder.build();
+        let expected = StackLayoutRef::new(&buffer, None);
+
+        let path = std::env::temp_dir().join(std::format!(
+            "linux-libc-auxv-mmap-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, &buffer).unwrap();
+        // SAFETY: The file is private to this test and not modified.
+        let mapped = unsafe { StackLayoutRef::from_mmap_file(&path) }.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let layout = mapped.as_layout_ref();
+        assert_eq!(layout.as_bytes(), &buffer[..]);
+        assert_eq!(layout.argc(), 1);
+        assert_eq!(layout.envc(), 1);
+        assert!(layout.argv_raw_iter().eq(expected.argv_raw_iter()));
+        assert_eq!(
+            layout.auxv_raw_iter().collect::<Vec<_>>(),
+            expected.auxv_raw_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "mmap is not supported by miri")]
+    fn test_from_mmap_file_empty() {
+        let path = std::env::temp_dir().join(std::format!(
+            "linux-libc-auxv-mmap-empty-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, []).unwrap();
+        // SAFETY: The file is private to this test and not modified.
+        let err = unsafe { StackLayoutRef::from_mmap_file(&path) }.unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,233 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::parser::{cstr_in_buffer, offset_in_buffer};
+use crate::{AuxVar, AuxVarRaw, AuxVarType, StackLayoutRef};
+use core::ffi::CStr;
+use core::iter::FusedIterator;
+
+/// A [`StackLayoutRef`] whose pointers are adjusted by a fixed offset.
+///
+/// This is useful if the layout was built for a `target_base` address but the
+/// buffer is located at `local_base`. Then, each pointer must be adjusted by
+/// `local_base - target_base` to be valid in the current address space.
+/// Created by [`StackLayoutRef::with_address_offset`].
+///
+/// All iterators and accessors emit adjusted pointers.
+#[derive(Debug)]
+pub struct OffsetStackLayoutRef<'a> {
+    layout: StackLayoutRef<'a>,
+    offset: isize,
+}
+
+impl<'a> OffsetStackLayoutRef<'a> {
+    pub(crate) const fn new(layout: StackLayoutRef<'a>, offset: isize) -> Self {
+        Self { layout, offset }
+    }
+
+    /// Returns the underlying [`StackLayoutRef`], which emits the unadjusted
+    /// pointers.
+    #[must_use]
+    pub const fn as_layout_ref(&self) -> &StackLayoutRef<'a> {
+        &self.layout
+    }
+
+    /// Returns the offset that is added to each pointer.
+    #[must_use]
+    pub const fn offset(&self) -> isize {
+        self.offset
+    }
+
+    /// Adjusts the raw address by the offset.
+    const fn adjust(&self, addr: usize) -> usize {
+        addr.wrapping_add_signed(self.offset)
+    }
+
+    /// Returns the [`CStr`] at the adjusted `addr`, if it is within the
+    /// underlying buffer.
+    fn cstr_at(&self, addr: usize) -> Option<&'a CStr> {
+        cstr_in_buffer(self.layout.as_bytes(), addr)
+    }
+
+    /// Returns the 16 random bytes at the adjusted `addr`, if they are within
+    /// the underlying buffer.
+    fn random_at(&self, addr: usize) -> Option<[u8; 16]> {
+        let buffer = self.layout.as_bytes();
+        let begin = offset_in_buffer(buffer, addr)?;
+        let bytes = buffer.get(begin..begin.checked_add(16)?)?;
+        bytes.try_into().ok()
+    }
+
+    /// Returns the number of arguments.
+    #[must_use]
+    pub fn argc(&self) -> usize {
+        self.layout.argc()
+    }
+
+    /// Returns the number of environment variables.
+    #[must_use]
+    pub fn envc(&self) -> usize {
+        self.layout.envc()
+    }
+
+    /// Returns the number of auxiliary vector entries.
+    #[must_use]
+    pub fn auxvc(&self) -> usize {
+        self.layout.auxvc()
+    }
+
+    /// Like [`StackLayoutRef::argv_raw_iter`] but emits adjusted pointers.
+    #[must_use]
+    pub fn argv_raw_iter(&self) -> impl ExactSizeIterator<Item = *const u8> + FusedIterator {
+        let offset = self.offset;
+        self.layout
+            .argv_raw_iter()
+            .map(move |ptr| ptr.wrapping_byte_offset(offset))
+    }
+
+    /// Like [`StackLayoutRef::envv_raw_iter`] but emits adjusted pointers.
+    #[must_use]
+    pub fn envv_raw_iter(&self) -> impl ExactSizeIterator<Item = *const u8> + FusedIterator {
+        let offset = self.offset;
+        self.layout
+            .envv_raw_iter()
+            .map(move |ptr| ptr.wrapping_byte_offset(offset))
+    }
+
+    /// Like [`StackLayoutRef::auxv_raw_iter`] but the values of entries that
+    /// point into the _auxv data area_ are adjusted.
+    ///
+    /// See [`AuxVarType::value_in_data_area`].
+    ///
+    /// [`AuxVarType::value_in_data_area`]: crate::AuxVarType::value_in_data_area
+    #[must_use]
+    pub fn auxv_raw_iter(&self) -> impl ExactSizeIterator<Item = AuxVarRaw> + FusedIterator {
+        self.layout.auxv_raw_iter().map(|raw| {
+            let key = raw.key().expect("should only emit known keys");
+            if key.value_in_data_area() {
+                AuxVarRaw::new(key, self.adjust(raw.value()))
+            } else {
+                raw
+            }
+        })
+    }
+
+    /// Returns an iterator over the arguments.
+    ///
+    /// Unlike [`StackLayoutRef::argv_iter`], this is safe: an adjusted pointer
+    /// that doesn't reference a string within the underlying buffer is emitted
+    /// as `None`.
+    #[must_use]
+    pub fn argv_iter(&self) -> impl ExactSizeIterator<Item = Option<&'a CStr>> {
+        self.argv_raw_iter().map(|ptr| self.cstr_at(ptr as usize))
+    }
+
+    /// Returns an iterator over the environment variables.
+    ///
+    /// Like for [`Self::argv_iter`], pointers that don't reference a string
+    /// within the underlying buffer are emitted as `None`.
+    #[must_use]
+    pub fn envv_iter(&self) -> impl ExactSizeIterator<Item = Option<&'a CStr>> {
+        self.envv_raw_iter().map(|ptr| self.cstr_at(ptr as usize))
+    }
+
+    /// Returns an iterator over the auxiliary variables.
+    ///
+    /// Like for [`Self::argv_iter`], entries whose adjusted pointer doesn't
+    /// reference a string, or 16 random bytes, within the underlying buffer
+    /// are emitted as `None`. All other entries are always `Some`.
+    #[must_use]
+    pub fn auxv_iter(&self) -> impl ExactSizeIterator<Item = Option<AuxVar<'a>>> {
+        self.auxv_raw_iter().map(|raw| {
+            let key = raw.key().expect("should only emit known keys");
+            match key {
+                AuxVarType::Platform => self
+                    .cstr_at(raw.value())
+                    .map(|s| AuxVar::Platform(s.into())),
+                AuxVarType::BasePlatform => self
+                    .cstr_at(raw.value())
+                    .map(|s| AuxVar::BasePlatform(s.into())),
+                AuxVarType::ExecFn => self.cstr_at(raw.value()).map(|s| AuxVar::ExecFn(s.into())),
+                AuxVarType::Random => self.random_at(raw.value()).map(AuxVar::Random),
+                // SAFETY: Only the values of the keys above are dereferenced.
+                _ => Some(unsafe { AuxVar::from_raw(&raw, self.layout.as_bytes()) }),
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use crate::{AuxVar, AuxVarRaw, AuxVarType, StackLayoutBuilder, StackLayoutRef};
+    use core::ffi::CStr;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_with_address_offset() {
+        const TARGET_BASE: usize = 0x4000_0000;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Platform("x86_64".into()));
+        let buffer = builder.build_at_address(TARGET_BASE);
+        let local_base = buffer.as_ptr() as usize;
+
+        let offset = local_base.wrapping_sub(TARGET_BASE) as isize;
+        let layout = StackLayoutRef::new(&buffer, None).with_address_offset(offset);
+        assert_eq!(layout.offset(), offset);
+        assert_eq!(layout.argc(), 1);
+        assert_eq!(layout.envc(), 1);
+        assert_eq!(layout.auxvc(), 2);
+        assert!(layout.argv_iter().eq([Some(c"./prog")]));
+        assert!(layout.envv_iter().eq([Some(c"FOO=bar")]));
+        assert!(layout.auxv_iter().eq([
+            Some(AuxVar::Pagesz(4096)),
+            Some(AuxVar::Platform(c"x86_64".into())),
+        ]));
+
+        let auxv = layout.auxv_raw_iter().collect::<Vec<_>>();
+        assert_eq!(auxv[0], AuxVarRaw::new(AuxVarType::Pagesz, 4096));
+        let platform = auxv[1].value() - local_base;
+        assert_eq!(
+            CStr::from_bytes_until_nul(&buffer[platform..]).unwrap(),
+            c"x86_64"
+        );
+
+        // Without the offset, the pointers don't reference the buffer.
+        let layout = StackLayoutRef::new(&buffer, None).with_address_offset(0);
+        assert!(layout.argv_iter().eq([None]));
+        assert!(layout.auxv_iter().eq([Some(AuxVar::Pagesz(4096)), None]));
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        let buffer = builder.build_at_address(TARGET_BASE);
+        let offset = (buffer.as_ptr() as usize).wrapping_sub(TARGET_BASE) as isize;
+        let layout = StackLayoutRef::new(&buffer, None).with_address_offset(offset);
+        assert!(layout.auxv_iter().eq([Some(AuxVar::Random([7; 16]))]));
+        // Too close to the end of the buffer for 16 bytes.
+        let layout = StackLayoutRef::new(&buffer, None).with_address_offset(offset + 1);
+        assert!(layout.auxv_iter().eq([None]));
+    }
+}
@@ -21,10 +21,255 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-use crate::aux_var::{AuxVar, AuxVarRaw, AuxVarType};
-use crate::util::count_bytes_until_null;
+use crate::aux_var::{AuxVar, AuxVarRaw, AuxVarString, AuxVarType};
+use crate::util::{Fnv1aHasher, count_bytes_until_null};
+use crate::{LocalStackLayout, OffsetStackLayoutRef, StackLayoutRef32, StackLayoutRef64};
+use core::borrow::Borrow;
 use core::ffi::CStr;
 use core::fmt::Debug;
+use core::hash::Hasher;
+use core::iter::FusedIterator;
+use core::str::Utf8Error;
+#[cfg(all(feature = "std", unix))]
+use {
+    crate::MmapLayoutRef,
+    std::{ffi::OsString, os::unix::ffi::OsStringExt},
+};
+#[cfg(feature = "alloc")]
+use {alloc::borrow::Cow, alloc::string::String, alloc::vec::Vec};
+#[cfg(feature = "std")]
+use {alloc::ffi::CString, core::fmt::Write, std::io};
+
+/// Errors that can happen when strings are read via a user-provided reader,
+/// for example in [`StackLayoutRef::read_argv_strings`].
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    /// The reader failed to read the memory.
+    #[error("failed to read memory at 0x{addr:x}")]
+    Io {
+        /// The address that was read.
+        addr: usize,
+        /// The underlying I/O error.
+        #[source]
+        source: io::Error,
+    },
+    /// No NUL byte was found within [`crate::MAX_ARG_STRLEN`] bytes.
+    #[error("string at 0x{0:x} is not NUL-terminated within MAX_ARG_STRLEN bytes")]
+    StringTooLong(usize),
+    /// No NUL byte was found before the end of the address space.
+    #[error("string at 0x{0:x} is not NUL-terminated within the address space")]
+    AddressOverflow(usize),
+}
+
+/// Error that is reported if a buffer is not aligned to
+/// `align_of::<usize>()`.
+///
+/// See [`StackLayoutRef::check_alignment`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("buffer at 0x{addr:x} is not aligned to usize")]
+pub struct AlignmentError {
+    /// The address of the misaligned buffer.
+    pub addr: usize,
+}
+
+/// Errors that [`StackLayoutRef::try_new`] reports for malformed stack
+/// layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum StackLayoutError {
+    /// The buffer is not aligned to `align_of::<usize>()`.
+    #[error(transparent)]
+    Misaligned(#[from] AlignmentError),
+    /// The buffer is too small to hold `argc`.
+    #[error("buffer is too small to hold argc")]
+    MissingArgc,
+    /// The `argv` array exceeds the buffer.
+    #[error("argv array is not terminated within the buffer")]
+    UnterminatedArgv,
+    /// The number of non-null `argv` entries doesn't match `argc`.
+    #[error("argv array doesn't match argc")]
+    ArgcMismatch,
+    /// The `envv` array exceeds the buffer.
+    #[error("envv array is not terminated within the buffer")]
+    UnterminatedEnvv,
+    /// The `auxv` array exceeds the buffer.
+    #[error("auxv array is not terminated within the buffer")]
+    UnterminatedAuxv,
+}
+
+/// Errors that [`StackLayoutRef::apply_patch`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PatchError {
+    /// The stack layout is malformed.
+    #[error(transparent)]
+    Layout(#[from] StackLayoutError),
+    /// There is no `auxv` entry of the given type.
+    #[error("auxv has no entry of type {0:?}")]
+    TypeNotFound(AuxVarType),
+}
+
+/// Errors that [`StackLayoutRef::parse_auxv_section_pod`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AuxvCastError {
+    /// The length of the buffer is not a multiple of the size of
+    /// [`AuxVarRaw`].
+    #[error("buffer length {0} is not a multiple of the auxv entry size")]
+    InvalidLength(usize),
+    /// There is no [`AuxVarType::Null`] entry.
+    #[error("auxv array is not terminated within the buffer")]
+    Unterminated,
+}
+
+/// A section of a stack layout with its raw bytes, as emitted by
+/// [`StackLayoutRef::iter_sections`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutSection<'a> {
+    /// The `argc` word.
+    Argc(&'a [u8]),
+    /// The `argv` entries, including the terminating null entry.
+    ArgvEntries(&'a [u8]),
+    /// The `envv` entries, including the terminating null entry.
+    EnvvEntries(&'a [u8]),
+    /// The `auxv` entries, including the terminating [`AuxVarType::Null`]
+    /// entry.
+    AuxvEntries(&'a [u8]),
+    /// The padding and the data areas until the end of the buffer.
+    DataArea(&'a [u8]),
+}
+
+impl<'a> LayoutSection<'a> {
+    /// Returns the raw bytes of the section.
+    #[must_use]
+    pub const fn bytes(&self) -> &'a [u8] {
+        match self {
+            Self::Argc(bytes)
+            | Self::ArgvEntries(bytes)
+            | Self::EnvvEntries(bytes)
+            | Self::AuxvEntries(bytes)
+            | Self::DataArea(bytes) => bytes,
+        }
+    }
+}
+
+/// The kind of a null-terminated array of a stack layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SectionKind {
+    /// The argument vector (`argv`).
+    Argv,
+    /// The environment vector (`envv`).
+    Envv,
+    /// The auxiliary vector (`auxv`).
+    Auxv,
+}
+
+/// The origin of a string emitted by [`StackLayoutRef::iter_all_strings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StringKind {
+    /// The argument at the given index of `argv`.
+    Argv(usize),
+    /// The environment variable at the given index of `envv`.
+    Envv(usize),
+    /// The value of [`AuxVarType::Platform`].
+    AuxvPlatform,
+    /// The value of [`AuxVarType::BasePlatform`].
+    AuxvBasePlatform,
+    /// The value of [`AuxVarType::ExecFn`].
+    AuxvExecFn,
+}
+
+/// Placement of the data areas relative to the entries of a stack layout,
+/// as guessed by [`StackLayoutRef::layout_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutFormat {
+    /// The data areas follow the entries, as created by Linux and the
+    /// [`StackLayoutBuilder`].
+    ///
+    /// [`StackLayoutBuilder`]: crate::StackLayoutBuilder
+    Standard,
+    /// The data areas precede the entries, as done by some non-standard
+    /// loaders.
+    DataAreaFirst,
+    /// There are no pointers, or they point to both sides of the entries.
+    Unknown,
+}
+
+/// An entry of a stack layout with its semantic meaning, as emitted by
+/// [`StackLayoutRef::zip_all`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutEntry {
+    /// The `argc` word.
+    Argc(usize),
+    /// The pointer of the argument with the given index.
+    Argv(usize, *const u8),
+    /// The pointer of the environment variable with the given index.
+    Envv(usize, *const u8),
+    /// An `auxv` entry with an immediate value.
+    AuxvImmediate(AuxVarType, usize),
+    /// An `auxv` entry whose value points into the _auxv data area_. See
+    /// [`AuxVarType::value_in_data_area`].
+    AuxvPointer(AuxVarType, usize),
+    /// The terminating null entry of the given array.
+    Null(SectionKind),
+}
+
+/// Aggregate of the most relevant properties of a process, as emitted by
+/// [`StackLayoutRef::process_info`].
+///
+/// Each field is `None` if the corresponding entry is not present in the
+/// layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessInfo<'a> {
+    /// The program name, i.e., `argv[0]`.
+    pub program_name: Option<&'a CStr>,
+    /// The value of [`AuxVarType::Platform`].
+    pub platform: Option<&'a CStr>,
+    /// The value of [`AuxVarType::Uid`].
+    pub uid: Option<u32>,
+    /// The value of [`AuxVarType::Gid`].
+    pub gid: Option<u32>,
+    /// The value of [`AuxVarType::Pagesz`].
+    pub page_size: Option<usize>,
+    /// The value of [`AuxVarType::HwCap`].
+    pub hwcap: Option<usize>,
+    /// The value of [`AuxVarType::HwCap2`].
+    pub hwcap2: Option<usize>,
+    /// The value of [`AuxVarType::Secure`].
+    pub is_secure: Option<bool>,
+    /// The bytes referenced by [`AuxVarType::Random`].
+    pub random_bytes: Option<[u8; 16]>,
+}
+
+/// Differences between two stack layouts, as emitted by
+/// [`StackLayoutRef::diff`].
+///
+/// Strings and `auxv` entries are compared by value, not by their pointers.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayoutDiff<'a> {
+    /// Arguments that are only present in the other layout.
+    pub added_argv: Vec<&'a CStr>,
+    /// Arguments that are only present in the original layout.
+    pub removed_argv: Vec<&'a CStr>,
+    /// Environment variables that are only present in the other layout.
+    pub added_envv: Vec<&'a CStr>,
+    /// Environment variables that are only present in the original layout.
+    pub removed_envv: Vec<&'a CStr>,
+    /// `auxv` entries whose type is only present in the other layout.
+    pub added_auxv: Vec<AuxVar<'a>>,
+    /// `auxv` entries whose type is only present in the original layout.
+    pub removed_auxv: Vec<AuxVar<'a>>,
+    /// `auxv` entries with different values as `(original, other)` pairs.
+    pub changed_auxv: Vec<(AuxVar<'a>, AuxVar<'a>)>,
+}
+
+#[cfg(feature = "alloc")]
+impl LayoutDiff<'_> {
+    /// Returns whether the layouts have the same content.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
 
 /// Wraps a slice of bytes representing a Linux stack layout allowing to
 /// conveniently parse its content.
@@ -50,6 +295,9 @@ use core::fmt::Debug;
 /// hold the stack layout. For example, passing in a 1 MiB slice is perfectly
 /// fine.
 ///
+/// The slice must be aligned to `align_of::<usize>()`, as it is the case for
+/// the real stack of a process. See [`Self::check_alignment`].
+///
 /// # Safety
 /// Each function that loads data from one of the
 ///
@@ -60,6 +308,9 @@ pub struct StackLayoutRef<'a> {
     // Might cover more data than the actual content of the stack layout.
     bytes: &'a [u8],
     argc: Option<usize>,
+    // The data areas if they precede `bytes`, see `new_strings_first()`.
+    // `None` means that the data areas follow the entries within `bytes`.
+    data_area: Option<&'a [u8]>,
 }
 
 impl<'a> StackLayoutRef<'a> {
@@ -67,10 +318,612 @@ impl<'a> StackLayoutRef<'a> {
     ///
     /// The `argc` determines whether `bytes` start with the `argc` argument
     /// (=> `None`) or if `bytes` already point to the start of `argv`.
+    ///
+    /// # Panics
+    /// Panics if `bytes` is not aligned to `align_of::<usize>()`. Use
+    /// [`Self::try_new`] to handle this gracefully.
     #[must_use]
     pub fn new(bytes: &'a [u8], argc: Option<usize>) -> Self {
-        assert_eq!(bytes.as_ptr().align_offset(align_of::<usize>()), 0);
-        Self { bytes, argc }
+        if let Err(e) = Self::check_alignment(bytes) {
+            panic!("{e}");
+        }
+        Self {
+            bytes,
+            argc,
+            data_area: None,
+        }
+    }
+
+    /// Creates a new view into a stack layout whose `argc` is stored as
+    /// 32-bit value in the first word of `bytes`.
+    ///
+    /// This is non-standard and only needed for compatibility with legacy
+    /// binaries. The first four bytes are read as `u32` and zero-extended,
+    /// the remaining bytes of the word are ignored. As the word is still
+    /// padded to its full size, `argv` starts at the second word and keeps
+    /// its alignment. The returned view doesn't cover the `argc` word, as if
+    /// it was created by [`Self::new`] with an explicit `argc`.
+    ///
+    /// # Panics
+    /// Panics if `bytes` is not aligned to `align_of::<usize>()` or is too
+    /// small to hold `argc`.
+    #[must_use]
+    pub fn new_argc32(bytes: &'a [u8]) -> Self {
+        let argc = bytes
+            .get(..size_of::<u32>())
+            .and_then(|argc| argc.try_into().ok())
+            .map(u32::from_ne_bytes)
+            .expect("buffer should contain argc");
+        let argv = bytes.get(size_of::<usize>()..).unwrap_or_default();
+        Self::new(argv, Some(argc as usize))
+    }
+
+    /// Checks that `bytes` is aligned to `align_of::<usize>()`, which is
+    /// required to view it as stack layout.
+    pub fn check_alignment(bytes: &[u8]) -> Result<(), AlignmentError> {
+        if bytes.as_ptr().align_offset(align_of::<usize>()) == 0 {
+            Ok(())
+        } else {
+            Err(AlignmentError {
+                addr: bytes.as_ptr() as usize,
+            })
+        }
+    }
+
+    /// Returns the underlying buffer.
+    ///
+    /// This is the buffer passed in on creation and might cover more data
+    /// than the actual content of the stack layout.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns a pointer to the start of the underlying buffer.
+    ///
+    /// See [`Self::as_bytes`].
+    #[must_use]
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.bytes.as_ptr()
+    }
+
+    /// Returns the first `bytes` bytes of the underlying buffer, or the whole
+    /// buffer if it is shorter.
+    ///
+    /// This is a testing utility for parsers that handle incomplete data, for
+    /// example, in combination with [`Self::try_new`]. The result may not be
+    /// a valid layout.
+    #[must_use]
+    pub fn truncate(&self, bytes: usize) -> &'a [u8] {
+        &self.bytes[..bytes.min(self.bytes.len())]
+    }
+
+    /// Returns the underlying buffer up to, but excluding, the `n`-th `argv`
+    /// entry.
+    ///
+    /// This is a testing utility like [`Self::truncate`]. The result may not
+    /// be a valid layout.
+    #[must_use]
+    pub fn truncate_at_argv(&self, n: usize) -> &'a [u8] {
+        let argc = if self.argc.is_none() {
+            size_of::<usize>()
+        } else {
+            0
+        };
+        self.truncate(n.saturating_mul(size_of::<usize>()).saturating_add(argc))
+    }
+
+    /// Returns the bytes of the `argv` entries, including the terminating
+    /// null entry.
+    #[must_use]
+    pub fn argv_entries_bytes(&self) -> &'a [u8] {
+        let len = (self.argc() + 1/* NULL */) * size_of::<usize>();
+        &self.get_slice_argv()[..len]
+    }
+
+    /// Returns the bytes of the `envv` entries, including the terminating
+    /// null entry.
+    #[must_use]
+    pub fn envv_entries_bytes(&self) -> &'a [u8] {
+        let len = (self.envc() + 1/* NULL */) * size_of::<usize>();
+        &self.get_slice_envv()[..len]
+    }
+
+    /// Returns the bytes of the `auxv` entries, including the terminating
+    /// [`AuxVarType::Null`] entry.
+    #[must_use]
+    pub fn auxv_entries_bytes(&self) -> &'a [u8] {
+        let len = (self.auxvc() + 1/* NULL */) * size_of::<AuxVarRaw>();
+        &self.get_slice_auxv()[..len]
+    }
+
+    /// Returns the `argv` entries, including the terminating null entry, as
+    /// slice of raw pointers without copying.
+    #[must_use]
+    pub fn argv_raw_entries(&self) -> &'a [usize] {
+        bytes_as_words(self.argv_entries_bytes())
+    }
+
+    /// Returns the `envv` entries, including the terminating null entry, as
+    /// slice of raw pointers without copying.
+    #[must_use]
+    pub fn envv_raw_entries(&self) -> &'a [usize] {
+        bytes_as_words(self.envv_entries_bytes())
+    }
+
+    /// Returns the `auxv` entries, including the terminating
+    /// [`AuxVarType::Null`] entry, as slice without copying.
+    ///
+    /// This enables direct indexing and searching.
+    #[must_use]
+    pub fn auxv_raw_entries(&self) -> &'a [AuxVarRaw] {
+        AuxVarRaw::slice_from_bytes(self.auxv_entries_bytes())
+            .expect("should be a multiple of the entry size")
+    }
+
+    /// Encodes the `auxv` entries in the binary format of `/proc/PID/auxv` of
+    /// 64-bit processes: back-to-back `[u64; 2]` pairs of type and value in
+    /// native byte order, terminated by a `[0, 0]` pair for
+    /// [`AuxVarType::Null`].
+    ///
+    /// On 64-bit hosts, this equals [`Self::auxv_entries_bytes`] for layouts
+    /// with a zero terminator.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn auxv_as_proc_format(&self) -> Vec<u8> {
+        let entries = &self.auxv_entries_bytes()[..self.auxvc() * size_of::<AuxVarRaw>()];
+        let mut bytes = Vec::with_capacity((self.auxvc() + 1) * 2 * size_of::<u64>());
+        for &word in bytes_as_words(entries).iter().chain(&[0, 0]) {
+            bytes.extend_from_slice(&(word as u64).to_ne_bytes());
+        }
+        bytes
+    }
+
+    /// Returns the `auxv` entries, excluding the terminating
+    /// [`AuxVarType::Null`] entry, as section that can be iterated safely.
+    ///
+    /// The section emits [`AuxVarRaw`] values only. For typed [`AuxVar`]s
+    /// with resolved pointers, use the unsafe [`Self::auxv_iter`].
+    #[must_use]
+    pub fn auxv_section(&self) -> AuxvSection<'a> {
+        let entries = self.auxv_raw_entries();
+        AuxvSection {
+            entries: &entries[..entries.len() - 1],
+        }
+    }
+
+    /// Returns the bytes following the `auxv` entries, i.e., the padding and
+    /// the data areas of `auxv`, `argv`, and `envv`.
+    ///
+    /// As the end of the structure is not encoded in the layout, the slice
+    /// ends at the end of the underlying buffer.
+    #[must_use]
+    pub fn data_bytes(&self) -> &'a [u8] {
+        let start = (self.auxvc() + 1/* NULL */) * size_of::<AuxVarRaw>();
+        &self.get_slice_auxv()[start..]
+    }
+
+    /// Returns an iterator over all entries of the layout with their semantic
+    /// meaning, in the order of their appearance in memory.
+    ///
+    /// This enables, for example, hex dumpers to annotate each word of the
+    /// buffer. If the layout was created with an explicit `argc`, there is no
+    /// [`LayoutEntry::Argc`] entry.
+    pub fn zip_all(&self) -> impl Iterator<Item = LayoutEntry> {
+        let argc = self.argc.is_none().then(|| LayoutEntry::Argc(self.argc()));
+        let auxv = self.auxv_raw_iter().map(|raw| {
+            let key = raw.key().expect("should only emit known keys");
+            if key.value_in_data_area() {
+                LayoutEntry::AuxvPointer(key, raw.value())
+            } else {
+                LayoutEntry::AuxvImmediate(key, raw.value())
+            }
+        });
+        argc.into_iter()
+            .chain(
+                self.argv_raw_iter()
+                    .enumerate()
+                    .map(|(i, ptr)| LayoutEntry::Argv(i, ptr)),
+            )
+            .chain(core::iter::once(LayoutEntry::Null(SectionKind::Argv)))
+            .chain(
+                self.envv_raw_iter()
+                    .enumerate()
+                    .map(|(i, ptr)| LayoutEntry::Envv(i, ptr)),
+            )
+            .chain(core::iter::once(LayoutEntry::Null(SectionKind::Envv)))
+            .chain(auxv)
+            .chain(core::iter::once(LayoutEntry::Null(SectionKind::Auxv)))
+    }
+
+    /// Returns the size in bytes of the entries, i.e., `argc` (if part of the
+    /// buffer) and the `argv`, `envv`, and `auxv` arrays including their
+    /// terminating null entries.
+    #[must_use]
+    pub fn entries_size(&self) -> usize {
+        let argc = if self.argc.is_none() {
+            size_of::<usize>()
+        } else {
+            0
+        };
+        argc + self.argv_entries_bytes().len()
+            + self.envv_entries_bytes().len()
+            + self.auxv_entries_bytes().len()
+    }
+
+    /// Returns the size in bytes of the combined data area, i.e., of the
+    /// strings of `argv` and `envv` and the payloads of `auxv`, including the
+    /// NUL bytes and any padding.
+    ///
+    /// This is computed from offsets only: it is the number of bytes of the
+    /// buffer after the entries, or the size of the data area before the
+    /// entries for [`Self::new_strings_first`]. No pointers are dereferenced,
+    /// so this also works for layouts of other address spaces. Hence, the sum
+    /// of [`Self::entries_size`] and this equals the size of the buffer.
+    #[must_use]
+    pub fn string_data_size(&self) -> usize {
+        self.data_area.map_or_else(
+            || self.bytes.len().saturating_sub(self.entries_size()),
+            <[u8]>::len,
+        )
+    }
+
+    /// Returns the size in bytes of the strings of `argv`, including the NUL
+    /// bytes.
+    ///
+    /// Like [`Self::string_data_size`], this is computed from the pointer
+    /// values only: each string extends to the next datum in the data area.
+    /// Padding at the end of the data area is attributed to the datum with
+    /// the highest address. Entries that point to the same datum, for example
+    /// with [`StackLayoutBuilder::with_string_pool`], count it once, for the
+    /// first of them. Hence, the sum of this, [`Self::envv_data_bytes`], and
+    /// [`Self::auxv_data_bytes`] equals [`Self::string_data_size`].
+    ///
+    /// [`StackLayoutBuilder::with_string_pool`]: crate::StackLayoutBuilder::with_string_pool
+    #[must_use]
+    pub fn argv_data_bytes(&self) -> usize {
+        self.data_extents().take(self.argc()).sum()
+    }
+
+    /// Returns the size in bytes of the strings of `envv`, including the NUL
+    /// bytes.
+    ///
+    /// This is computed like [`Self::argv_data_bytes`].
+    #[must_use]
+    pub fn envv_data_bytes(&self) -> usize {
+        self.data_extents()
+            .skip(self.argc())
+            .take(self.envc())
+            .sum()
+    }
+
+    /// Returns the size in bytes of the payloads of `auxv`, i.e., of the
+    /// strings including their NUL bytes and of the random bytes.
+    ///
+    /// This is computed like [`Self::argv_data_bytes`].
+    #[must_use]
+    pub fn auxv_data_bytes(&self) -> usize {
+        self.data_extents().skip(self.argc() + self.envc()).sum()
+    }
+
+    /// Returns the size in bytes that the strings of `argv` occupy in the
+    /// data area.
+    ///
+    /// This is the same as [`Self::argv_data_bytes`].
+    #[must_use]
+    pub fn size_of_argv_data_area(&self) -> usize {
+        self.argv_data_bytes()
+    }
+
+    /// Returns the size in bytes that the strings of `envv` occupy in the
+    /// data area.
+    ///
+    /// This is the same as [`Self::envv_data_bytes`].
+    #[must_use]
+    pub fn size_of_envv_data_area(&self) -> usize {
+        self.envv_data_bytes()
+    }
+
+    /// Returns the size in bytes that the payloads of `auxv` occupy in the
+    /// data area.
+    ///
+    /// This is the same as [`Self::auxv_data_bytes`].
+    #[must_use]
+    pub fn size_of_auxv_data_area(&self) -> usize {
+        self.auxv_data_bytes()
+    }
+
+    /// Returns the average length in bytes of the arguments, excluding the
+    /// NUL bytes, or zero if there are no arguments.
+    ///
+    /// This is based on [`Self::argv_data_bytes`].
+    #[must_use]
+    pub fn avg_argv_len(&self) -> usize {
+        let argc = self.argc();
+        if argc == 0 {
+            return 0;
+        }
+        self.argv_data_bytes().saturating_sub(argc /* NUL */) / argc
+    }
+
+    /// Returns an iterator over the sections of the layout in the order of
+    /// their appearance in memory.
+    ///
+    /// The concatenation of all sections equals [`Self::as_bytes`]. If the
+    /// layout was created with an explicit `argc`, there is no
+    /// [`LayoutSection::Argc`] section.
+    pub fn iter_sections(&self) -> impl Iterator<Item = LayoutSection<'a>> {
+        let argc = match self.argc {
+            None => Some(LayoutSection::Argc(&self.bytes[..size_of::<usize>()])),
+            Some(_) => None,
+        };
+        [
+            argc,
+            Some(LayoutSection::ArgvEntries(self.argv_entries_bytes())),
+            Some(LayoutSection::EnvvEntries(self.envv_entries_bytes())),
+            Some(LayoutSection::AuxvEntries(self.auxv_entries_bytes())),
+            Some(LayoutSection::DataArea(self.data_bytes())),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Splits the layout into a view of `argc` and `argv`, and a view of
+    /// `envv` and `auxv`.
+    ///
+    /// Both views borrow the underlying buffer until its end, as the data
+    /// areas follow the entries. This enables processing both parts
+    /// independently, e.g., in parallel.
+    #[must_use]
+    pub fn split_at_envv(&self) -> (ArgvView<'a>, EnvvAuxvView<'a>) {
+        let argv = ArgvView {
+            argc: self.argc(),
+            argv: self.get_slice_argv(),
+        };
+        let envv = EnvvAuxvView {
+            envv: self.get_slice_envv(),
+        };
+        (argv, envv)
+    }
+
+    /// Converts the layout into a [`LocalStackLayout`] if all pointers of
+    /// `argv`, `envv`, and `auxv` reference data within the underlying buffer.
+    ///
+    /// Returns `None` if the structure is malformed (see [`Self::try_new`])
+    /// or if a pointer references memory outside the buffer.
+    #[must_use]
+    pub fn into_local(self) -> Option<LocalStackLayout<'a>> {
+        (self.validate().is_ok() && self.pointers_are_local())
+            .then(|| LocalStackLayout::new_unchecked(self))
+    }
+
+    /// Wraps the layout into an [`OffsetStackLayoutRef`] that adjusts all
+    /// pointers by `offset`.
+    ///
+    /// This is useful if the layout was built for another address, e.g., by
+    /// [`StackLayoutBuilder::build_at_address`], but is inspected in the
+    /// buffer of the current address space.
+    ///
+    /// [`StackLayoutBuilder::build_at_address`]: crate::StackLayoutBuilder::build_at_address
+    #[must_use]
+    pub const fn with_address_offset(self, offset: isize) -> OffsetStackLayoutRef<'a> {
+        OffsetStackLayoutRef::new(self, offset)
+    }
+
+    /// Checks that all pointers reference data within the respective section
+    /// of the underlying buffer that the iterators operate on.
+    fn pointers_are_local(&self) -> bool {
+        let argv = self.get_slice_argv();
+        let envv = self.get_slice_envv();
+        let auxv = self.get_slice_auxv();
+        self.argv_raw_iter()
+            .all(|ptr| is_cstr_in_buffer(argv, ptr as usize))
+            && self
+                .envv_raw_iter()
+                .all(|ptr| is_cstr_in_buffer(envv, ptr as usize))
+            && self.auxv_raw_iter().all(|raw| match raw.key() {
+                Ok(AuxVarType::Random) => offset_in_buffer(auxv, raw.value())
+                    .is_some_and(|offset| offset + 16 <= auxv.len()),
+                Ok(key) if key.value_is_cstr() => is_cstr_in_buffer(auxv, raw.value()),
+                _ => true,
+            })
+    }
+
+    /// Creates a new view into the stack layout from a slice of words, where
+    /// the first word is `argc`.
+    ///
+    /// This is a zero-cost reinterpretation of the words as bytes.
+    #[must_use]
+    pub const fn from_words(words: &'a [usize]) -> Self {
+        // SAFETY: Any initialized memory is a valid byte slice and the
+        // alignment of `usize` is sufficient.
+        let bytes =
+            unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), size_of_val(words)) };
+        Self {
+            bytes,
+            argc: None,
+            data_area: None,
+        }
+    }
+
+    /// Creates a new view into a stack layout of a 32-bit process from a slice
+    /// of 32-bit words, where the first word is `argc`.
+    ///
+    /// This works independent of the pointer width of the host. As
+    /// [`StackLayoutRef32`] reads little-endian words, the words must be
+    /// in little-endian byte order, which is the native one on little-endian
+    /// hosts. For a buffer of bytes, use [`StackLayoutRef32::new`].
+    #[must_use]
+    pub const fn from_words32(words: &'a [u32]) -> StackLayoutRef32<'a> {
+        // SAFETY: Any initialized memory is a valid byte slice.
+        let bytes =
+            unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), size_of_val(words)) };
+        StackLayoutRef32::new(bytes)
+    }
+
+    /// Creates a new view into a stack layout of a 64-bit process from a slice
+    /// of 64-bit words, where the first word is `argc`.
+    ///
+    /// This works independent of the pointer width of the host. As
+    /// [`StackLayoutRef64`] reads little-endian words, the words must be
+    /// in little-endian byte order, which is the native one on little-endian
+    /// hosts. For a buffer of bytes, use [`StackLayoutRef64::new`].
+    #[must_use]
+    pub const fn from_words64(words: &'a [u64]) -> StackLayoutRef64<'a> {
+        // SAFETY: Any initialized memory is a valid byte slice.
+        let bytes =
+            unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), size_of_val(words)) };
+        StackLayoutRef64::new(bytes)
+    }
+
+    /// Creates a new view into a stack layout where the data areas precede
+    /// the entries, as done by some non-standard loaders, see
+    /// [`LayoutFormat::DataAreaFirst`].
+    ///
+    /// The start of the entries, i.e., `argc`, is detected heuristically by
+    /// scanning backward from the end of `bytes`, where the entries must end:
+    /// the last pair of words must be the [`AuxVarType::Null`] entry. Before
+    /// it, all pairs with known keys belong to `auxv`, which is preceded by
+    /// the null entry of `envv`. The non-null words before that are the
+    /// entries of `envv`, up to the null entry of `argv`. Finally, the first
+    /// word before the null entry of `argv` that equals the number of words
+    /// in between is `argc`. The detected layout is then validated once with
+    /// [`Self::try_new`], and all its pointers must reference the bytes before
+    /// the entries, with the strings of `argv` and `envv` being NUL-terminated
+    /// there. Hence, this takes linear time in the size of `bytes`.
+    ///
+    /// The heuristic assumes that `bytes` is located at the address the
+    /// layout was built for. Returns `None` if the scan fails or if `bytes` is
+    /// not aligned.
+    ///
+    /// The returned view covers the entries, e.g., in [`Self::as_bytes`], but
+    /// keeps the preceding data areas. Unsafe functions such as
+    /// [`Self::argv_iter`] resolve the pointers within these data areas and
+    /// panic for pointers outside of them.
+    #[must_use]
+    pub fn new_strings_first(bytes: &'a [u8]) -> Option<Self> {
+        const WORD_SIZE: usize = size_of::<usize>();
+
+        if bytes.len() % WORD_SIZE != 0 {
+            return None;
+        }
+        let word = |index: usize| read_usize(bytes, index * WORD_SIZE);
+        let is_key = |word: usize| word != 0 && AuxVarType::try_from(word).is_ok();
+
+        // auxv: the null entry, preceded by the entries with known keys
+        let mut index = (bytes.len() / WORD_SIZE).checked_sub(2)?;
+        if word(index)? != 0 {
+            return None;
+        }
+        while index >= 2 && is_key(word(index - 2)?) {
+            index -= 2;
+        }
+        // envv: the null entry, preceded by the non-null entries
+        index = index.checked_sub(1)?;
+        if word(index)? != 0 {
+            return None;
+        }
+        loop {
+            index = index.checked_sub(1)?;
+            if word(index)? == 0 {
+                break;
+            }
+        }
+        // argv: the entries before its null entry, preceded by `argc`
+        let mut argc = 0;
+        loop {
+            index = index.checked_sub(1)?;
+            match word(index)? {
+                value if value == argc => break,
+                0 => return None,
+                _ => argc += 1,
+            }
+        }
+
+        let offset = index * WORD_SIZE;
+        let layout = Self::try_new(&bytes[offset..], None).ok()?;
+        if layout.entries_size() != bytes.len() - offset {
+            return None;
+        }
+        let start = bytes.as_ptr() as usize;
+        let data = &bytes[..offset];
+        let data_offset = |ptr: usize| ptr.checked_sub(start).filter(|&o| o < offset);
+        let pointers_valid = layout.data_pointers().all(|ptr| data_offset(ptr).is_some());
+        let strings_valid = layout
+            .argv_raw_iter()
+            .chain(layout.envv_raw_iter())
+            .filter_map(|ptr| data_offset(ptr as usize))
+            .all(|o| count_bytes_until_null(&data[o..]).is_some());
+        (pointers_valid && strings_valid).then_some(Self {
+            data_area: Some(data),
+            ..layout
+        })
+    }
+
+    /// Fallible version of [`Self::new`] that validates the structure of the
+    /// stack layout.
+    ///
+    /// On success, it is guaranteed that all entries of `argv`, `envv`, and
+    /// `auxv` are within `bytes`. Hence, [`Self::argc`], [`Self::envc`],
+    /// [`Self::auxvc`], and the raw iterators won't panic. The referenced
+    /// data, such as the strings, is not validated.
+    pub fn try_new(bytes: &'a [u8], argc: Option<usize>) -> Result<Self, StackLayoutError> {
+        Self::check_alignment(bytes)?;
+        let layout = Self {
+            bytes,
+            argc,
+            data_area: None,
+        };
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    /// Validates that all entries of the layout are within the buffer.
+    fn validate(&self) -> Result<(), StackLayoutError> {
+        const WORD_SIZE: usize = size_of::<usize>();
+
+        if self.argc.is_none() && read_usize(self.bytes, 0).is_none() {
+            return Err(StackLayoutError::MissingArgc);
+        }
+        let argc = self.argc();
+        let argv = self.get_slice_argv();
+
+        // argv: `argc` non-null entries followed by a null entry
+        let envv_offset = argc
+            .checked_add(1)
+            .and_then(|entries| entries.checked_mul(WORD_SIZE))
+            .filter(|&offset| offset <= argv.len())
+            .ok_or(StackLayoutError::UnterminatedArgv)?;
+        let argv_consistent = (0..argc).all(|i| read_usize(argv, i * WORD_SIZE) != Some(0))
+            && read_usize(argv, argc * WORD_SIZE) == Some(0);
+        if !argv_consistent {
+            return Err(StackLayoutError::ArgcMismatch);
+        }
+
+        // envv: non-null entries followed by a null entry
+        let mut offset = envv_offset;
+        loop {
+            let entry = read_usize(argv, offset).ok_or(StackLayoutError::UnterminatedEnvv)?;
+            offset += WORD_SIZE;
+            if entry == 0 {
+                break;
+            }
+        }
+
+        // auxv: entries until the [`AuxVarType::Null`] entry or the first
+        // entry that the iterators can't parse
+        loop {
+            if offset + size_of::<AuxVarRaw>() > argv.len() {
+                return Err(StackLayoutError::UnterminatedAuxv);
+            }
+            let key = read_usize(argv, offset).unwrap();
+            offset += size_of::<AuxVarRaw>();
+            if !matches!(AuxVarType::try_from(key), Ok(key) if key != AuxVarType::Null) {
+                break;
+            }
+        }
+
+        Ok(())
     }
 
     // ========== BEGIN buffer get functions ==========
@@ -121,27 +974,39 @@ impl<'a> StackLayoutRef<'a> {
     /// Returns the number of arguments.
     #[must_use]
     pub fn argc(&self) -> usize {
-        self.argc.unwrap_or_else(|| unsafe {
-            // the first `usize` is the `argc` argument
-            self.bytes
-                .as_ptr()
-                .cast::<usize>()
-                .as_ref()
-                .copied()
-                .unwrap()
-        })
+        // the first `usize` is the `argc` argument
+        self.argc
+            .unwrap_or_else(|| read_usize(self.bytes, 0).expect("buffer should contain argc"))
+    }
+
+    /// Returns the exact `argc` value, regardless of any sign interpretation.
+    ///
+    /// This is the same as [`Self::argc`] and exists for symmetry with
+    /// [`Self::argc_signed`].
+    #[must_use]
+    pub fn argc_raw(&self) -> usize {
+        self.argc()
+    }
+
+    /// Returns `argc` as signed value.
+    ///
+    /// This is a compatibility helper for unusual targets whose ABI specifies
+    /// a signed `argc`. Values that exceed [`i64::MAX`] saturate.
+    #[must_use]
+    pub fn argc_signed(&self) -> i64 {
+        i64::try_from(self.argc_raw()).unwrap_or(i64::MAX)
     }
 
     /// Returns the number of environment variables.
     #[must_use]
     pub fn envc(&self) -> usize {
-        self.envv_raw_iter().count()
+        self.envv_raw_iter().len()
     }
 
     /// Returns the number of auxiliary vector entries.
     #[must_use]
     pub fn auxvc(&self) -> usize {
-        self.auxv_raw_iter().count()
+        self.auxv_raw_iter().len()
     }
 
     /// Returns an iterator over the raw argument vector's (`argv`)
@@ -151,7 +1016,8 @@ impl<'a> StackLayoutRef<'a> {
     /// The pointers must point to valid memory. If dereferenced, the memory
     /// **must** be in the address space of the application. Otherwise,
     /// segmentation faults or UB will occur.
-    pub fn argv_raw_iter(&self) -> impl Iterator<Item = *const u8> {
+    #[must_use]
+    pub fn argv_raw_iter(&self) -> impl ExactSizeIterator<Item = *const u8> + FusedIterator {
         let buffer = self.get_slice_argv();
         unsafe { NullTermArrIter::new(buffer) }
     }
@@ -163,7 +1029,8 @@ impl<'a> StackLayoutRef<'a> {
     /// The pointers must point to valid memory. If dereferenced, the memory
     /// **must** be in the address space of the application. Otherwise,
     /// segmentation faults or UB will occur.
-    pub fn envv_raw_iter(&self) -> impl Iterator<Item = *const u8> {
+    #[must_use]
+    pub fn envv_raw_iter(&self) -> impl ExactSizeIterator<Item = *const u8> + FusedIterator {
         let buffer = self.get_slice_envv();
         unsafe { NullTermArrIter::new(buffer) }
     }
@@ -175,481 +1042,3079 @@ impl<'a> StackLayoutRef<'a> {
     /// Any pointers must point to valid memory. If dereferenced, the memory
     /// **must** be in the address space of the application. Otherwise,
     /// segmentation faults or UB will occur.
-    pub fn auxv_raw_iter(&self) -> impl Iterator<Item = AuxVarRaw> {
+    #[must_use]
+    pub fn auxv_raw_iter(&self) -> impl ExactSizeIterator<Item = AuxVarRaw> + FusedIterator {
         AuxVarRawIter::new(self.get_slice_auxv())
     }
 
-    /// Unsafe version of [`Self::argv_raw_iter`] that only works if all pointers
-    /// are valid. It emits high-level items of type [`CStr`].
-    ///
-    /// This is typically safe if you parse the stack layout you've got from
-    /// Linux but not if you parse some other's stack layout.
+    /// Like [`Self::argv_raw_iter`] but additionally emits `None` for the
+    /// terminating null entry.
+    pub fn argv_raw_iter_including_null(&self) -> impl Iterator<Item = Option<*const u8>> {
+        self.argv_raw_iter().map(Some).chain(core::iter::once(None))
+    }
+
+    /// Like [`Self::envv_raw_iter`] but additionally emits `None` for the
+    /// terminating null entry.
+    pub fn envv_raw_iter_including_null(&self) -> impl Iterator<Item = Option<*const u8>> {
+        self.envv_raw_iter().map(Some).chain(core::iter::once(None))
+    }
+
+    /// Returns the raw pointer of the `n`-th argument (`argv[n]`) or `None` if
+    /// `n` is out of bounds.
     ///
-    /// # Safety
-    /// The pointers must point to valid memory. If dereferenced, the memory
-    /// **must** be in the address space of the application. Otherwise,
-    /// segmentation faults or UB will occur.
-    pub unsafe fn argv_iter(&self) -> impl Iterator<Item = &'a CStr> {
-        let buffer = self.get_slice_argv();
-        unsafe { CStrArrayIter::new(buffer) }
+    /// The entry is read directly, as the length of `argv` is known by
+    /// `argc`.
+    #[must_use]
+    pub fn nth_argv_raw(&self, n: usize) -> Option<usize> {
+        (n < self.argc()).then(|| {
+            read_usize(self.get_slice_argv(), n * size_of::<usize>())
+                .expect("argv array ended prematurely")
+        })
     }
-    /// Unsafe version of [`Self::envv_raw_iter`] that only works if all pointers
-    /// are valid. It emits high-level items of type [`CStr`].
+
+    /// Returns the `n`-th argument (`argv[n]`) if its pointer references a
+    /// NUL-terminated string within the backing buffer.
     ///
-    /// This is typically safe if you parse the stack layout you've got from
-    /// Linux but not if you parse some other's stack layout.
+    /// Returns `None` if `n` is out of bounds or if the pointer points
+    /// somewhere else. This is safe, as no external memory is dereferenced.
+    #[must_use]
+    pub fn argv_nth_in_buffer(&self, n: usize) -> Option<&'a CStr> {
+        let addr = self.nth_argv_raw(n)?;
+        cstr_in_buffer(self.bytes, addr)
+    }
+
+    /// Returns the raw pointer of the `n`-th environment variable or `None`
+    /// if `n` is out of bounds.
     ///
-    /// # Safety
-    /// The pointers must point to valid memory. If dereferenced, the memory
-    /// **must** be in the address space of the application. Otherwise,
-    /// segmentation faults or UB will occur.
-    pub unsafe fn envv_iter(&self) -> impl Iterator<Item = &'a CStr> {
-        let buffer = self.get_slice_envv();
-        unsafe { CStrArrayIter::new(buffer) }
+    /// Unlike for [`Self::nth_argv_raw`], the length of `envv` must be
+    /// determined by a scan of the array first.
+    #[must_use]
+    pub fn nth_envv_raw(&self, n: usize) -> Option<usize> {
+        self.envv_raw_iter().nth(n).map(|ptr| ptr as usize)
     }
 
-    /// Unsafe version of [`Self::argv_raw_iter`] that only works if all pointers
-    /// are valid. It emits high-level items of type [`AuxVar`].
+    /// Returns the `n`-th `auxv` entry or `None` if `n` is out of bounds.
     ///
-    /// This is typically safe if you parse the stack layout you've got from
-    /// Linux but not if you parse some other's stack layout.
+    /// As `auxv` follows `envv`, this requires a scan of `envv` and of the
+    /// `auxv` array itself first.
+    #[must_use]
+    pub fn nth_auxv_raw(&self, n: usize) -> Option<AuxVarRaw> {
+        self.auxv_raw_iter().nth(n)
+    }
+
+    /// Returns the 0-based index of the first `auxv` entry of the given type.
     ///
-    /// # Safety
-    /// Any pointers must point to valid memory. If dereferenced, the memory
-    /// **must** be in the address space of the application. Otherwise,
-    /// segmentation faults or UB will occur.
-    pub unsafe fn auxv_iter(&self) -> impl Iterator<Item = AuxVar<'a>> {
-        unsafe { AuxVarIter::new(self.get_slice_auxv()) }
+    /// This enables checks of the relative order of entries.
+    #[must_use]
+    pub fn auxv_index_of(&self, key: AuxVarType) -> Option<usize> {
+        self.auxv_raw_iter().position(|raw| raw.key() == Ok(key))
     }
-}
 
-/// Iterator over the entries of a null-terminated array of pointers.
-///
-/// This should not be used to read the raw pointer into a [`CStr`], so that
-/// Miri can verify all our memory accesses are valid.
-#[derive(Debug)]
-struct NullTermArrIter<'a> {
-    // Buffer holds more bytes than necessary because the size of the auxv
-    // array is not known at compile time.
-    buffer: &'a [u8],
-    i: usize,
-}
+    /// Returns the `auxv` entries that carry an immediate value, such as
+    /// [`AuxVarType::Uid`] or [`AuxVarType::Pagesz`], as `(key, value)`
+    /// pairs.
+    ///
+    /// Entries that reference the _auxv data area_ are skipped. Hence, unlike
+    /// [`Self::auxv_iter`], this is safe.
+    #[must_use]
+    pub fn auxv_iter_immediate(&self) -> impl FusedIterator<Item = (AuxVarType, usize)> {
+        self.auxv_raw_iter().filter_map(|raw| {
+            raw.key()
+                .ok()
+                .filter(|key| !key.value_in_data_area())
+                .map(|key| (key, raw.value()))
+        })
+    }
 
-impl<'a> NullTermArrIter<'a> {
-    // SAFETY: If the pointers point to invalid memory, UB will occur.
-    unsafe fn new(buffer: &'a [u8]) -> Self {
-        assert_eq!(buffer.as_ptr().align_offset(align_of::<usize>()), 0);
+    /// Returns whether there is an `auxv` entry of the given type.
+    #[must_use]
+    pub fn contains_auxv(&self, key: AuxVarType) -> bool {
+        self.auxv_index_of(key).is_some()
+    }
 
-        Self { buffer, i: 0 }
+    /// Returns whether there are `auxv` entries of all the given types.
+    #[must_use]
+    pub fn contains_all_auxv(&self, keys: &[AuxVarType]) -> bool {
+        keys.iter().all(|&key| self.contains_auxv(key))
     }
-}
 
-impl Iterator for NullTermArrIter<'_> {
-    type Item = *const u8;
+    /// Overwrites the values of `auxv` entries in-place for each
+    /// `(type, new_value)` pair, without rebuilding the layout.
+    ///
+    /// As a [`StackLayoutRef`] only borrows its buffer immutably, this takes
+    /// the mutable `bytes` of a layout whose first word is `argc`. The first
+    /// entry of each type is patched. Either all or none of the patches are
+    /// applied.
+    ///
+    /// # Returns
+    /// The number of applied patches.
+    ///
+    /// # Errors
+    /// Returns [`PatchError::Layout`] if [`Self::try_new`] fails and
+    /// [`PatchError::TypeNotFound`] if there is no entry of a requested type.
+    pub fn apply_patch(
+        bytes: &mut [u8],
+        patches: &[(AuxVarType, usize)],
+    ) -> Result<usize, PatchError> {
+        // `AuxVarType::MinSigStkSz` has the highest value of all types.
+        const KEYS: usize = AuxVarType::MinSigStkSz.val() + 1;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.i >= self.buffer.len() {
-            panic!("null terminated array ended prematurely");
+        // A single scan of the validated layout determines the offset of the
+        // value of the first entry of each type.
+        let layout = StackLayoutRef::try_new(bytes, None)?;
+        let auxv_offset = (3 + layout.argc() + layout.envc()) * size_of::<usize>();
+        let mut value_offsets = [None; KEYS];
+        for (index, raw) in layout.auxv_raw_iter().enumerate() {
+            let key = raw.key().expect("iterator should stop at invalid keys");
+            value_offsets[key.val()]
+                .get_or_insert(auxv_offset + index * size_of::<AuxVarRaw>() + size_of::<usize>());
         }
-
-        let entry_ptr = unsafe {
-            self.buffer
-                .as_ptr()
-                .cast::<*const u8>()
-                // skip i pointers
-                .add(self.i)
-        };
-        let entry = unsafe { entry_ptr.as_ref().copied().unwrap() };
-        if entry.is_null() {
-            return None;
+        if let Some(&(key, _)) = patches
+            .iter()
+            .find(|(key, _)| value_offsets[key.val()].is_none())
+        {
+            return Err(PatchError::TypeNotFound(key));
         }
 
-        self.i += 1;
-        Some(entry)
+        for &(key, value) in patches {
+            let offset = value_offsets[key.val()].expect("should have been checked");
+            bytes[offset..offset + size_of::<usize>()].copy_from_slice(&value.to_ne_bytes());
+        }
+        Ok(patches.len())
     }
-}
-
-/// Iterator over the [`CStr`]s of a null-terminated C-style array.
-///
-/// This should only be used when you know that the memory being referenced is
-/// valid. Otherwise, segmentation faults or UB occur.
-#[derive(Debug)]
-struct CStrArrayIter<'a> {
-    // Buffer holds more bytes than necessary because the size of the auxv
-    // array is not known at compile time.
-    buffer: &'a [u8],
-    i: usize,
-}
 
-impl<'a> CStrArrayIter<'a> {
-    // SAFETY: If the pointers point to invalid memory, UB will occur.
-    unsafe fn new(buffer: &'a [u8]) -> Self {
-        assert_eq!(buffer.as_ptr().align_offset(align_of::<usize>()), 0);
+    /// Reinterprets `bytes` that only contain an `auxv` array, such as the
+    /// content of `/proc/self/auxv`, as [`AuxVarRaw`] entries without copying.
+    ///
+    /// Unlike the entry-by-entry parsing of [`Self::auxv_raw_iter`], this is a
+    /// single cast via [`AuxVarRaw::slice_from_bytes`], which also works for
+    /// unaligned buffers. The returned entries end with the first
+    /// [`AuxVarType::Null`] entry, including it. Any bytes after it are
+    /// ignored.
+    ///
+    /// # Errors
+    /// Returns [`AuxvCastError::InvalidLength`] if the length of `bytes` is not
+    /// a multiple of the entry size and [`AuxvCastError::Unterminated`] if
+    /// there is no terminating entry.
+    pub fn parse_auxv_section_pod(bytes: &[u8]) -> Result<&[AuxVarRaw], AuxvCastError> {
+        let entries =
+            AuxVarRaw::slice_from_bytes(bytes).ok_or(AuxvCastError::InvalidLength(bytes.len()))?;
+        let null_index = entries
+            .iter()
+            .position(|raw| raw.key_raw() == AuxVarType::Null.val())
+            .ok_or(AuxvCastError::Unterminated)?;
+        Ok(&entries[..=null_index])
+    }
 
-        Self { buffer, i: 0 }
+    /// Returns the raw value of the first `auxv` entry of the given type.
+    fn auxv_raw_value(&self, key: AuxVarType) -> Option<usize> {
+        self.auxv_raw_iter()
+            .find(|raw| raw.key() == Ok(key))
+            .map(|raw| raw.value())
     }
-}
 
-impl<'a> Iterator for CStrArrayIter<'a> {
-    type Item = &'a CStr;
+    /// Returns the base address of the program interpreter, i.e., the dynamic
+    /// linker, from [`AuxVarType::Base`].
+    #[must_use]
+    pub fn at_base(&self) -> Option<usize> {
+        self.auxv_raw_value(AuxVarType::Base)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.i >= self.buffer.len() {
-            panic!("null terminated array ended prematurely");
-        }
+    /// Returns the address of the entry point of the program from
+    /// [`AuxVarType::Entry`].
+    #[must_use]
+    pub fn at_entry(&self) -> Option<usize> {
+        self.auxv_raw_value(AuxVarType::Entry)
+    }
 
-        let entry_ptr = unsafe { self.buffer.as_ptr().cast::<*const u8>().add(self.i) };
-        let entry = unsafe { entry_ptr.as_ref().copied().unwrap() };
-        if entry.is_null() {
-            return None;
-        }
+    /// Returns the address of the system call entry point in the vDSO from
+    /// [`AuxVarType::Sysinfo`].
+    #[must_use]
+    pub fn at_sysinfo(&self) -> Option<usize> {
+        self.auxv_raw_value(AuxVarType::Sysinfo)
+    }
 
-        // Assert in range
-        {
-            let end = &raw const self.buffer[self.buffer.len() - 1];
-            assert!(entry > self.buffer.as_ptr());
-            assert!(entry <= end);
-        }
+    /// Unsafe version of [`Self::argv_raw_iter`] that only works if all pointers
+    /// are valid. It emits high-level items of type [`CStr`].
+    ///
+    /// This is typically safe if you parse the stack layout you've got from
+    /// Linux but not if you parse some other's stack layout.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_iter(&self) -> impl ExactSizeIterator<Item = &'a CStr> + FusedIterator {
+        let buffer = self.get_slice_argv();
+        unsafe { CStrArrayIter::with_data(buffer, self.data_area.unwrap_or(buffer)) }
+    }
 
-        // offset of the pointer within the buffer
-        let begin_index = entry as usize - self.buffer.as_ptr() as usize;
-        let end_index_rel =
-            count_bytes_until_null(&self.buffer[begin_index..]).expect("should have NUL byte");
-        let end_index = begin_index + end_index_rel + 1 /* NUL byte */;
-        let cstr = CStr::from_bytes_with_nul(&self.buffer[begin_index..end_index]).unwrap();
+    /// Returns the `n`-th argument (`argv[n]`) by dereferencing its pointer.
+    ///
+    /// Returns `None` if `n` is out of bounds or if the entry is the null
+    /// terminator. Use [`Self::argv_nth_in_buffer`] for layouts that come
+    /// from a different address space.
+    ///
+    /// # Safety
+    /// The pointer must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_nth_cstr(&self, n: usize) -> Option<&'a CStr> {
+        let addr = self.nth_argv_raw(n).filter(|&addr| addr != 0)?;
+        // SAFETY: The caller guarantees that the pointer is valid.
+        Some(unsafe { CStr::from_ptr(addr as *const _) })
+    }
 
-        self.i += 1;
-        Some(cstr)
+    /// Like [`Self::argv_iter`] but also emits the index of each argument,
+    /// which is its slot in `argv`, i.e., `0..argc`.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_iter_indexed(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (usize, &'a CStr)> + FusedIterator {
+        unsafe { self.argv_iter() }.enumerate()
     }
-}
 
-/// Iterates over the `auxv` array with dynamic size until the end key is found.
-///
-/// Emits elements of type [`AuxVarRaw`].
-#[derive(Debug)]
-pub struct AuxVarRawIter<'a> {
-    // Buffer holds more bytes than necessary because the size of the auxv
-    // array is not known at compile time.
-    auxv: &'a [u8],
-    i: usize,
-}
+    /// Unsafe version of [`Self::envv_raw_iter`] that only works if all pointers
+    /// are valid. It emits high-level items of type [`CStr`].
+    ///
+    /// This is typically safe if you parse the stack layout you've got from
+    /// Linux but not if you parse some other's stack layout.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_iter(&self) -> impl ExactSizeIterator<Item = &'a CStr> + FusedIterator {
+        let buffer = self.get_slice_envv();
+        unsafe { CStrArrayIter::with_data(buffer, self.data_area.unwrap_or(buffer)) }
+    }
 
-impl<'a> AuxVarRawIter<'a> {
-    const fn new(auxv: &'a [u8]) -> Self {
-        Self { auxv, i: 0 }
+    /// Like [`Self::envv_iter`] but also emits the index of each environment
+    /// variable, which is its slot in `envv`.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_iter_indexed(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (usize, &'a CStr)> + FusedIterator {
+        unsafe { self.envv_iter() }.enumerate()
     }
-}
 
-impl<'a> Iterator for AuxVarRawIter<'a> {
-    type Item = AuxVarRaw;
+    /// Like [`Self::argv_iter`] but emits the arguments as UTF-8 [`str`]s.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_iter_str(
+        &self,
+    ) -> impl ExactSizeIterator<Item = Result<&'a str, Utf8Error>> + FusedIterator {
+        unsafe { self.argv_iter() }.map(CStr::to_str)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let entry = unsafe {
-            let entry_ptr = self.auxv.as_ptr().cast::<AuxVarRaw>().add(self.i);
-            entry_ptr.as_ref().unwrap()
-        };
+    /// Like [`Self::envv_iter`] but emits the environment variables as UTF-8
+    /// [`str`]s.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_iter_str(
+        &self,
+    ) -> impl ExactSizeIterator<Item = Result<&'a str, Utf8Error>> + FusedIterator {
+        unsafe { self.envv_iter() }.map(CStr::to_str)
+    }
 
-        if let Ok(key) = entry.key() {
-            if key == AuxVarType::Null {
-                None
-            } else {
-                self.i += 1;
-                Some(*entry)
-            }
-        } else {
-            // log error?
-            // invalid data, stop
-            None
-        }
+    /// Like [`Self::argv_iter_str`] but invalid UTF-8 is replaced by
+    /// [`char::REPLACEMENT_CHARACTER`], which is suitable for display and
+    /// logging.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub unsafe fn argv_iter_lossy(
+        &self,
+    ) -> impl ExactSizeIterator<Item = Cow<'a, str>> + FusedIterator {
+        unsafe { self.argv_iter() }.map(CStr::to_string_lossy)
     }
-}
 
-/// Iterates the [`AuxVar`]s of the stack layout.
-#[derive(Debug)]
-pub struct AuxVarIter<'a> {
-    // Buffer holds more bytes than necessary because the size of the auxv
-    // array is not known at compile time.
-    auxv: &'a [u8],
-    serialized_iter: AuxVarRawIter<'a>,
-}
+    /// Like [`Self::envv_iter_str`] but invalid UTF-8 is replaced by
+    /// [`char::REPLACEMENT_CHARACTER`], which is suitable for display and
+    /// logging.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub unsafe fn envv_iter_lossy(
+        &self,
+    ) -> impl ExactSizeIterator<Item = Cow<'a, str>> + FusedIterator {
+        unsafe { self.envv_iter() }.map(CStr::to_string_lossy)
+    }
 
-impl<'a> AuxVarIter<'a> {
-    // SAFETY: If the pointers point to invalid memory, UB will occur.
-    const unsafe fn new(auxv: &'a [u8]) -> Self {
-        Self {
-            serialized_iter: AuxVarRawIter::new(auxv),
-            auxv,
-        }
+    /// Copies all arguments into owned [`String`]s, which outlive the layout.
+    ///
+    /// Invalid UTF-8 is replaced by [`char::REPLACEMENT_CHARACTER`], as in
+    /// [`Self::argv_iter_lossy`].
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub unsafe fn argv_iter_owned(&self) -> Vec<String> {
+        unsafe { self.argv_iter_lossy() }
+            .map(Cow::into_owned)
+            .collect()
     }
-}
 
-impl<'a> Iterator for AuxVarIter<'a> {
-    type Item = AuxVar<'a>;
+    /// Copies all environment variables into owned [`String`]s, which outlive
+    /// the layout.
+    ///
+    /// Invalid UTF-8 is replaced by [`char::REPLACEMENT_CHARACTER`], as in
+    /// [`Self::envv_iter_lossy`].
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub unsafe fn envv_iter_owned(&self) -> Vec<String> {
+        unsafe { self.envv_iter_lossy() }
+            .map(Cow::into_owned)
+            .collect()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            self.serialized_iter
-                .next()
-                .map(|ref x| AuxVar::from_raw(x, self.auxv))
-        }
+    /// Returns the first argument that starts with `prefix`, e.g., `--flag-`.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_starts_with(&self, prefix: &str) -> Option<&'a CStr> {
+        unsafe { self.argv_iter() }.find(|arg| arg.to_bytes().starts_with(prefix.as_bytes()))
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Returns the first environment variable whose key starts with `prefix`,
+    /// e.g., `JAVA_`.
+    ///
+    /// The key is everything before the first `=`. For entries without a `=`,
+    /// the key is the whole string. The returned [`CStr`] is the complete
+    /// `KEY=VALUE` entry.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_starts_with_key(&self, prefix: &str) -> Option<&'a CStr> {
+        unsafe { self.envv_iter() }.find(|env| env_key(env).starts_with(prefix.as_bytes()))
+    }
+
+    /// Returns whether any argument exactly matches `arg`.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_contains(&self, arg: &str) -> bool {
+        unsafe { self.argv_iter() }.any(|other| other.to_bytes() == arg.as_bytes())
+    }
+
+    /// Returns whether any environment variable has the given key. Keys are
+    /// determined as in [`Self::envv_starts_with_key`].
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_contains_key(&self, key: &str) -> bool {
+        unsafe { self.envv_iter() }.any(|env| env_key(env) == key.as_bytes())
+    }
+
+    /// Unsafe version of [`Self::argv_raw_iter`] that only works if all pointers
+    /// are valid. It emits high-level items of type [`AuxVar`].
+    ///
+    /// This is typically safe if you parse the stack layout you've got from
+    /// Linux but not if you parse some other's stack layout.
+    ///
+    /// # Safety
+    /// Any pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn auxv_iter(&self) -> impl ExactSizeIterator<Item = AuxVar<'a>> + FusedIterator {
+        let buffer = self.get_slice_auxv();
+        unsafe { AuxVarIter::with_data(buffer, self.data_area.unwrap_or(buffer)) }
+    }
+
+    /// Copies all [`AuxVar`]s, including their string and byte payloads, into
+    /// owned values, which outlive the layout.
+    ///
+    /// # Safety
+    /// Any pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub unsafe fn auxv_iter_owned(&self) -> Vec<AuxVar<'static>> {
+        unsafe { self.auxv_iter() }
+            .map(AuxVar::into_owned)
+            .collect()
+    }
+
+    /// Iterates all strings of the layout together with their origin: first
+    /// the arguments, then the environment variables, and then the
+    /// string-backed entries of the auxiliary vector in their order in `auxv`.
+    ///
+    /// This allows to process all strings uniformly, e.g., to redact secrets.
+    /// Strings that are not valid UTF-8 are skipped.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn iter_all_strings(&self) -> impl FusedIterator<Item = (&'a str, StringKind)> {
+        let argv = unsafe { self.argv_iter() }
+            .enumerate()
+            .map(|(i, arg)| (arg, StringKind::Argv(i)));
+        let envv = unsafe { self.envv_iter() }
+            .enumerate()
+            .map(|(i, env)| (env, StringKind::Envv(i)));
+        let auxv = unsafe { self.auxv_iter() }.filter_map(|aux| match aux {
+            AuxVar::Platform(AuxVarString::CStr(str)) => Some((str, StringKind::AuxvPlatform)),
+            AuxVar::BasePlatform(AuxVarString::CStr(str)) => {
+                Some((str, StringKind::AuxvBasePlatform))
+            }
+            AuxVar::ExecFn(AuxVarString::CStr(str)) => Some((str, StringKind::AuxvExecFn)),
+            _ => None,
+        });
+        argv.chain(envv)
+            .chain(auxv)
+            .filter_map(|(str, kind)| Some((str.to_str().ok()?, kind)))
+    }
+
+    /// Collects the most relevant properties of the process into a
+    /// [`ProcessInfo`].
+    ///
+    /// IDs that don't fit into a `u32` are reported as `None`.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn process_info(&self) -> ProcessInfo<'a> {
+        let mut info = ProcessInfo {
+            program_name: unsafe { self.argv_iter() }.next(),
+            ..ProcessInfo::default()
+        };
+        for aux in unsafe { self.auxv_iter() } {
+            match aux {
+                AuxVar::Platform(AuxVarString::CStr(str)) => info.platform = Some(str),
+                AuxVar::Uid(uid) => info.uid = u32::try_from(uid).ok(),
+                AuxVar::Gid(gid) => info.gid = u32::try_from(gid).ok(),
+                AuxVar::Pagesz(size) => info.page_size = Some(size),
+                AuxVar::HwCap(bits) => info.hwcap = Some(bits),
+                AuxVar::HwCap2(bits) => info.hwcap2 = Some(bits),
+                AuxVar::Secure(secure) => info.is_secure = Some(secure),
+                AuxVar::Random(bytes) => info.random_bytes = Some(bytes),
+                _ => {}
+            }
+        }
+        info
+    }
+
+    /// Compares the content of the layout with the `other` layout.
+    ///
+    /// Arguments and environment variables are compared as multisets, i.e.,
+    /// their order is irrelevant. `auxv` entries are matched by their type;
+    /// for duplicate types, only the first entry is considered.
+    ///
+    /// # Safety
+    /// The pointers of both layouts must point to valid memory. If
+    /// dereferenced, the memory **must** be in the address space of the
+    /// application. Otherwise, segmentation faults or UB will occur.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub unsafe fn diff<'b>(&self, other: &StackLayoutRef<'b>) -> LayoutDiff<'b>
+    where
+        'a: 'b,
+    {
+        /// Returns the strings only in `new` and the strings only in `old`.
+        fn diff_strs<'x>(
+            old: impl Iterator<Item = &'x CStr>,
+            new: impl Iterator<Item = &'x CStr>,
+        ) -> (Vec<&'x CStr>, Vec<&'x CStr>) {
+            let mut removed = old.collect::<Vec<_>>();
+            let mut added = Vec::new();
+            for str in new {
+                match removed.iter().position(|&old| old == str) {
+                    Some(i) => {
+                        removed.remove(i);
+                    }
+                    None => added.push(str),
+                }
+            }
+            (added, removed)
+        }
+
+        let mut diff = LayoutDiff::default();
+        unsafe {
+            (diff.added_argv, diff.removed_argv) = diff_strs(self.argv_iter(), other.argv_iter());
+            (diff.added_envv, diff.removed_envv) = diff_strs(self.envv_iter(), other.envv_iter());
+
+            let old = self.auxv_iter().collect::<Vec<_>>();
+            let new = other.auxv_iter().collect::<Vec<_>>();
+            let find = |auxv: &[AuxVar<'b>], key| auxv.iter().find(|aux| aux.key() == key).cloned();
+            for (i, aux) in old.iter().enumerate() {
+                if old[..i].iter().any(|prev| prev.key() == aux.key()) {
+                    continue;
+                }
+                match find(&new, aux.key()) {
+                    Some(other) if &other != aux => diff.changed_auxv.push((aux.clone(), other)),
+                    Some(_) => {}
+                    None => diff.removed_auxv.push(aux.clone()),
+                }
+            }
+            for (i, aux) in new.iter().enumerate() {
+                if !new[..i].iter().any(|prev| prev.key() == aux.key())
+                    && find(&old, aux.key()).is_none()
+                {
+                    diff.added_auxv.push(aux.clone());
+                }
+            }
+        }
+        diff
+    }
+
+    /// Computes a deterministic hash of the logical content of the layout.
+    ///
+    /// The hash covers `argc`, the strings of `argv` and `envv`, and the
+    /// `auxv` entries by value. Pointers into the data areas are not hashed,
+    /// only the data they point to. Hence, two layouts with the same content
+    /// at different addresses have the same hash.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::new();
+        hasher.write_usize(self.argc());
+        for arg in unsafe { self.argv_iter() } {
+            hasher.write(arg.to_bytes_with_nul());
+        }
+        hasher.write_usize(self.envc());
+        for env in unsafe { self.envv_iter() } {
+            hasher.write(env.to_bytes_with_nul());
+        }
+        hasher.write_usize(self.auxvc());
+        for aux in unsafe { self.auxv_iter() } {
+            hasher.write_usize(aux.key().val());
+            if let Some(str) = aux.value_payload_str() {
+                hasher.write(&str.as_bytes()[..str.count_bytes()]);
+            } else if let Some(bytes) = aux.value_payload_bytes() {
+                hasher.write(bytes);
+            } else {
+                hasher.write_usize(aux.value_raw());
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Computes a deterministic hash of the entries of the layout, i.e., of
+    /// `argc` and the `argv`, `envv`, and `auxv` arrays, but not of the data
+    /// they point to.
+    ///
+    /// Pointers into the data areas are hashed relative to the lowest of
+    /// them. Hence, two layouts that store the same strings at different
+    /// addresses, e.g., built by [`StackLayoutBuilder::build_at_address`],
+    /// have the same checksum if the strings are at the same relative
+    /// positions. Unlike [`Self::content_hash`], no pointer is dereferenced.
+    ///
+    /// The algorithm is the 64-bit FNV-1a hash over the native-endian words.
+    ///
+    /// [`StackLayoutBuilder::build_at_address`]: crate::StackLayoutBuilder::build_at_address
+    #[must_use]
+    pub fn checksum_entries(&self) -> u64 {
+        let is_ptr = |raw: &AuxVarRaw| raw.key().is_ok_and(AuxVarType::value_in_data_area);
+        let base = self.data_pointers().min().unwrap_or(0);
+
+        let mut hasher = Fnv1aHasher::new();
+        hasher.write_usize(self.argc());
+        for ptr in self.argv_raw_iter() {
+            hasher.write_usize(ptr as usize - base);
+        }
+        hasher.write_usize(self.envc());
+        for ptr in self.envv_raw_iter() {
+            hasher.write_usize(ptr as usize - base);
+        }
+        hasher.write_usize(self.auxvc());
+        for raw in self.auxv_raw_iter() {
+            let key = raw.key().expect("iterator should stop at invalid keys");
+            hasher.write_usize(key.val());
+            if is_ptr(&raw) {
+                hasher.write_usize(raw.value() - base);
+            } else {
+                hasher.write_usize(raw.value());
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Guesses where the data areas are located relative to the entries.
+    ///
+    /// The heuristic compares all pointers of `argv`, `envv`, and `auxv` with
+    /// the address range of the entries in the underlying buffer. If all of
+    /// them point behind the entries, the format is
+    /// [`LayoutFormat::Standard`]; if all of them point before the entries,
+    /// it is [`LayoutFormat::DataAreaFirst`].
+    ///
+    /// The heuristic assumes that the buffer is located at the address the
+    /// layout was built for. For a layout of a different address space, e.g.,
+    /// as created by [`StackLayoutBuilder::build_at_address`], the result is
+    /// meaningless. Layouts without any pointers are always
+    /// [`LayoutFormat::Unknown`].
+    ///
+    /// [`StackLayoutBuilder::build_at_address`]: crate::StackLayoutBuilder::build_at_address
+    #[must_use]
+    pub fn layout_format(&self) -> LayoutFormat {
+        let start = self.as_ptr() as usize;
+        let end = start + self.entries_size();
+        let (mut before, mut behind) = (false, false);
+        for ptr in self.data_pointers() {
+            before |= ptr < start;
+            behind |= ptr >= end;
+        }
+        match (before, behind) {
+            (false, true) => LayoutFormat::Standard,
+            (true, false) => LayoutFormat::DataAreaFirst,
+            _ => LayoutFormat::Unknown,
+        }
+    }
+
+    /// Returns the extent in the data area of each pointer of
+    /// [`Self::data_pointers`], in the same order.
+    ///
+    /// A datum extends to the next higher pointer. The datum with the highest
+    /// address extends to the end of the data area, i.e., the extents sum up
+    /// to [`Self::string_data_size`]. Pointers that equal an earlier pointer
+    /// have an extent of zero.
+    fn data_extents(&self) -> impl Iterator<Item = usize> {
+        let base = self.data_pointers().min().unwrap_or(0);
+        let end = base.saturating_add(self.string_data_size());
+        self.data_pointers().enumerate().map(move |(index, ptr)| {
+            if self.data_pointers().take(index).any(|other| other == ptr) {
+                return 0;
+            }
+            self.data_pointers()
+                .filter(|&other| other > ptr)
+                .min()
+                .unwrap_or(end)
+                .saturating_sub(ptr)
+        })
+    }
+
+    /// Returns all pointers of `argv`, `envv`, and `auxv` into the data areas.
+    fn data_pointers(&self) -> impl Iterator<Item = usize> {
+        self.argv_raw_iter()
+            .chain(self.envv_raw_iter())
+            .map(|ptr| ptr as usize)
+            .chain(
+                self.auxv_raw_iter()
+                    .filter(|raw| raw.key().is_ok_and(AuxVarType::value_in_data_area))
+                    .map(|raw| raw.value()),
+            )
+    }
+}
+
+#[cfg(feature = "std")]
+impl StackLayoutRef<'_> {
+    /// Reads the NUL-terminated string at `addr` using `reader`.
+    ///
+    /// The memory is read in small chunks that never cross a page boundary,
+    /// so that no unmapped memory is touched after the NUL byte.
+    fn read_cstring<F>(addr: usize, reader: &F) -> Result<CString, ReadError>
+    where
+        F: Fn(usize, &mut [u8]) -> io::Result<()>,
+    {
+        const CHUNK_SIZE: usize = 64;
+        const PAGE_SIZE: usize = 4096;
+
+        let mut bytes = Vec::new();
+        let mut chunk = [0_u8; CHUNK_SIZE];
+        loop {
+            let chunk_addr = addr
+                .checked_add(bytes.len())
+                .ok_or(ReadError::AddressOverflow(addr))?;
+            let len = CHUNK_SIZE.min(PAGE_SIZE - chunk_addr % PAGE_SIZE);
+            let chunk = &mut chunk[..len];
+            reader(chunk_addr, chunk).map_err(|source| ReadError::Io {
+                addr: chunk_addr,
+                source,
+            })?;
+
+            match count_bytes_until_null(chunk) {
+                Some(end) => {
+                    bytes.extend_from_slice(&chunk[..end]);
+                    // There is no interim NUL byte by construction.
+                    return Ok(CString::new(bytes).unwrap());
+                }
+                None => bytes.extend_from_slice(chunk),
+            }
+
+            if bytes.len() >= crate::MAX_ARG_STRLEN {
+                return Err(ReadError::StringTooLong(addr));
+            }
+        }
+    }
+
+    /// Reads all strings of the argument vector (`argv`) using `reader`.
+    ///
+    /// Only the entries of the stack layout itself are parsed from the
+    /// underlying buffer. The referenced strings are read via `reader`, which
+    /// receives the address in the target address space and the buffer to
+    /// fill. This is useful if the stack layout belongs to another process,
+    /// for example one that is traced via `ptrace`.
+    pub fn read_argv_strings<F>(&self, reader: F) -> Result<Vec<CString>, ReadError>
+    where
+        F: Fn(usize, &mut [u8]) -> io::Result<()>,
+    {
+        self.argv_raw_iter()
+            .map(|ptr| Self::read_cstring(ptr as usize, &reader))
+            .collect()
+    }
+
+    /// Reads all strings of the environment vector (`envv`) using `reader`.
+    ///
+    /// See [`Self::read_argv_strings`].
+    pub fn read_envv_strings<F>(&self, reader: F) -> Result<Vec<CString>, ReadError>
+    where
+        F: Fn(usize, &mut [u8]) -> io::Result<()>,
+    {
+        self.envv_raw_iter()
+            .map(|ptr| Self::read_cstring(ptr as usize, &reader))
+            .collect()
+    }
+
+    /// Reads all strings referenced by the auxiliary vector (`auxv`) using
+    /// `reader`.
+    ///
+    /// Only entries where [`AuxVarType::value_is_cstr`] is true are
+    /// considered. See [`Self::read_argv_strings`].
+    pub fn read_auxv_strings<F>(&self, reader: F) -> Result<Vec<(AuxVarType, CString)>, ReadError>
+    where
+        F: Fn(usize, &mut [u8]) -> io::Result<()>,
+    {
+        self.auxv_raw_iter()
+            .filter_map(|raw| raw.key().ok().map(|key| (key, raw.value())))
+            .filter(|(key, _)| key.value_is_cstr())
+            .map(|(key, addr)| Self::read_cstring(addr, &reader).map(|str| (key, str)))
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl StackLayoutRef<'_> {
+    /// Maps the file at `path` into memory and views its content as stack
+    /// layout without copying.
+    ///
+    /// The layout is validated as in [`Self::try_new`]. Violations are
+    /// reported as [`io::ErrorKind::InvalidData`].
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated, neither by this nor by
+    /// another process, while the returned [`MmapLayoutRef`] is alive.
+    /// Otherwise, the bytes of the layout change underneath it, which is UB,
+    /// or accesses fail with `SIGBUS`.
+    pub unsafe fn from_mmap_file(path: &std::path::Path) -> io::Result<MmapLayoutRef> {
+        // SAFETY: The caller guarantees that the file is not modified.
+        unsafe { MmapLayoutRef::open(path) }
+    }
+
+    /// Returns copies of all arguments as [`OsString`]s, which is the natural
+    /// type for passing them to other OS APIs.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn argv_as_os_strings(&self) -> Vec<OsString> {
+        unsafe { self.argv_iter() }
+            .map(|arg| OsString::from_vec(arg.to_bytes().to_vec()))
+            .collect()
+    }
+
+    /// Returns copies of all environment variables as [`OsString`]s.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_as_os_strings(&self) -> Vec<OsString> {
+        unsafe { self.envv_iter() }
+            .map(|env| OsString::from_vec(env.to_bytes().to_vec()))
+            .collect()
+    }
+
+    /// Returns copies of all environment variables as `(key, value)` pairs,
+    /// e.g., for [`std::process::Command::envs`].
+    ///
+    /// Each entry is split at the first `=`. For entries without a `=`, the
+    /// key is the whole string and the value is empty.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_as_env_pairs(&self) -> Vec<(OsString, OsString)> {
+        unsafe { self.envv_iter() }
+            .map(|env| {
+                let bytes = env.to_bytes();
+                let (key, value) = bytes
+                    .iter()
+                    .position(|&b| b == b'=')
+                    .map_or((bytes, &[][..]), |pos| (&bytes[..pos], &bytes[pos + 1..]));
+                (
+                    OsString::from_vec(key.to_vec()),
+                    OsString::from_vec(value.to_vec()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns whether `key` is a valid shell identifier, i.e., matches
+/// `[A-Za-z_][A-Za-z0-9_]*`.
+#[cfg(feature = "std")]
+fn is_shell_identifier(key: &str) -> bool {
+    key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        && key.bytes().next().is_some_and(|b| !b.is_ascii_digit())
+}
+
+#[cfg(feature = "std")]
+impl StackLayoutRef<'_> {
+    /// Formats all environment variables as `export KEY='VALUE'` lines, e.g.,
+    /// for setup scripts.
+    ///
+    /// Single quotes in values are escaped as `'"'"'`. Entries without a `=`
+    /// get an empty value. Invalid UTF-8 is replaced by
+    /// [`char::REPLACEMENT_CHARACTER`].
+    ///
+    /// Entries whose key is not a valid shell identifier, i.e., does not match
+    /// `[A-Za-z_][A-Za-z0-9_]*`, are skipped, as they can't be exported and
+    /// could inject commands into the script.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn format_as_shell_env(&self) -> String {
+        let mut out = String::new();
+        for env in unsafe { self.envv_iter() } {
+            let env = String::from_utf8_lossy(env.to_bytes());
+            let (key, value) = env.split_once('=').unwrap_or((&env, ""));
+            if !is_shell_identifier(key) {
+                continue;
+            }
+            let value = value.replace('\'', "'\"'\"'");
+            // Writing into a `String` never fails.
+            let _ = writeln!(out, "export {key}='{value}'");
+        }
+        out
+    }
+
+    /// Formats all environment variables as `KEY=VALUE` lines without any
+    /// quoting, as in `.env` files.
+    ///
+    /// Invalid UTF-8 is replaced by [`char::REPLACEMENT_CHARACTER`].
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn format_as_dotenv(&self) -> String {
+        let mut out = String::new();
+        for env in unsafe { self.envv_iter() } {
+            out.push_str(&String::from_utf8_lossy(env.to_bytes()));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Prints all entries of the auxiliary vector (`auxv`) to stderr.
+    ///
+    /// Each entry is printed on its own line with its type and decoded value,
+    /// similar to the output of `LD_SHOW_AUXV=1`. This is meant as a debugging
+    /// helper.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    pub unsafe fn print_auxv(&self) {
+        let mut stderr = io::stderr().lock();
+        for aux in unsafe { self.auxv_iter() } {
+            // Errors are irrelevant for a debugging helper.
+            let _ = Self::write_aux_var(&mut stderr, &aux);
+        }
+    }
+
+    /// Like [`Self::print_auxv`] but only prints entries with an immediate
+    /// value, i.e., no entries referencing the `auxv` data area.
+    pub fn print_auxv_safe(&self) {
+        let mut stderr = io::stderr().lock();
+        for aux in self.auxv_immediate_vars() {
+            // Errors are irrelevant for a debugging helper.
+            let _ = Self::write_aux_var(&mut stderr, &aux);
+        }
+    }
+
+    /// Like [`Self::auxv_iter_immediate`] but emits typed [`AuxVar`]s.
+    fn auxv_immediate_vars(&self) -> impl Iterator<Item = AuxVar<'_>> {
+        self.auxv_iter_immediate().map(|(key, value)| {
+            // SAFETY: Immediate values are never dereferenced.
+            unsafe { AuxVar::from_raw(&AuxVarRaw::new(key, value), self.bytes) }
+        })
+    }
+
+    /// Writes a single entry in the format of [`Self::print_auxv`].
+    fn write_aux_var(writer: &mut impl io::Write, aux: &AuxVar<'_>) -> io::Result<()> {
+        let key = std::format!("{:?}:", aux.key());
+        if let Some(str) = aux.value_payload_str() {
+            let str = &str.as_bytes()[..str.count_bytes()];
+            writeln!(writer, "{key:<20}{}", String::from_utf8_lossy(str))
+        } else if let Some(bytes) = aux.value_payload_bytes() {
+            write!(writer, "{key:<20}")?;
+            for byte in bytes {
+                write!(writer, "{byte:02x}")?;
+            }
+            writeln!(writer)
+        } else if let Some(flags) = aux.value_flags() {
+            writeln!(writer, "{key:<20}{flags:?}")
+        } else if let Some(ptr) = aux.value_ptr() {
+            writeln!(writer, "{key:<20}{ptr:?}")
+        } else {
+            writeln!(writer, "{key:<20}{}", aux.value_raw())
+        }
+    }
+}
+
+/// Returns the offset of `addr` within `buffer`, if it is within the buffer
+/// and not its very first byte.
+pub(crate) fn offset_in_buffer(buffer: &[u8], addr: usize) -> Option<usize> {
+    addr.checked_sub(buffer.as_ptr() as usize)
+        .filter(|&offset| offset > 0 && offset < buffer.len())
+}
+
+/// Returns the NUL-terminated string that `addr` references within `buffer`.
+pub(crate) fn cstr_in_buffer(buffer: &[u8], addr: usize) -> Option<&CStr> {
+    let offset = offset_in_buffer(buffer, addr)?;
+    let len = count_bytes_until_null(&buffer[offset..])?;
+    CStr::from_bytes_with_nul(&buffer[offset..=offset + len]).ok()
+}
+
+/// Checks that `addr` references a NUL-terminated string within `buffer`.
+fn is_cstr_in_buffer(buffer: &[u8], addr: usize) -> bool {
+    cstr_in_buffer(buffer, addr).is_some()
+}
+
+/// Returns the key of the environment variable `env`, i.e., everything before
+/// the first `=` or the whole string if there is none.
+fn env_key(env: &CStr) -> &[u8] {
+    let bytes = env.to_bytes();
+    bytes
+        .iter()
+        .position(|&b| b == b'=')
+        .map_or(bytes, |pos| &bytes[..pos])
+}
+
+/// Reinterprets the aligned `bytes` as words without copying.
+fn bytes_as_words(bytes: &[u8]) -> &[usize] {
+    assert_eq!(bytes.as_ptr().align_offset(align_of::<usize>()), 0);
+    assert_eq!(bytes.len() % size_of::<usize>(), 0);
+    // SAFETY: The bytes are aligned, any bit pattern is a valid `usize`, and
+    // the length is a multiple of the word size.
+    unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast(), bytes.len() / size_of::<usize>()) }
+}
+
+/// Reads the `usize` at the given byte offset, if it is within `bytes`.
+fn read_usize(bytes: &[u8], offset: usize) -> Option<usize> {
+    let end = offset.checked_add(size_of::<usize>())?;
+    let bytes = bytes.get(offset..end)?;
+    Some(usize::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+impl AsRef<[u8]> for StackLayoutRef<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl Borrow<[u8]> for StackLayoutRef<'_> {
+    fn borrow(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+/// Iterator over the entries of a null-terminated array of pointers.
+///
+/// This should not be used to read the raw pointer into a [`CStr`], so that
+/// Miri can verify all our memory accesses are valid.
+#[derive(Debug)]
+struct NullTermArrIter<'a> {
+    // Buffer holds more bytes than necessary because the size of the auxv
+    // array is not known at compile time.
+    buffer: &'a [u8],
+    i: usize,
+    // Number of non-null entries, determined by the initial scan.
+    len: usize,
+}
+
+impl<'a> NullTermArrIter<'a> {
+    // SAFETY: If the pointers point to invalid memory, UB will occur.
+    unsafe fn new(buffer: &'a [u8]) -> Self {
+        assert_eq!(buffer.as_ptr().align_offset(align_of::<usize>()), 0);
+
+        let len = (0..)
+            .map(|i| {
+                read_usize(buffer, i * size_of::<*const u8>())
+                    .expect("null terminated array ended prematurely")
+            })
+            .take_while(|&entry| entry != 0)
+            .count();
+        Self { buffer, i: 0, len }
+    }
+}
+
+impl Iterator for NullTermArrIter<'_> {
+    type Item = *const u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i == self.len {
+            return None;
+        }
+
+        let entry_ptr = unsafe {
+            self.buffer
+                .as_ptr()
+                .cast::<*const u8>()
+                // skip i pointers
+                .add(self.i)
+        };
+        let entry = unsafe { entry_ptr.as_ref().copied().unwrap() };
+
+        self.i += 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.i;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // The entries are in a contiguous array, so we can skip them directly.
+        self.i = self.i.saturating_add(n).min(self.len);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for NullTermArrIter<'_> {}
+
+impl FusedIterator for NullTermArrIter<'_> {}
+
+/// Iterator over the [`CStr`]s of a null-terminated C-style array.
+///
+/// This should only be used when you know that the memory being referenced is
+/// valid. Otherwise, segmentation faults or UB occur.
+#[derive(Debug)]
+struct CStrArrayIter<'a> {
+    // Buffer holds more bytes than necessary because the size of the auxv
+    // array is not known at compile time. The pointers must reference it.
+    buffer: &'a [u8],
+    entries: NullTermArrIter<'a>,
+}
+
+impl<'a> CStrArrayIter<'a> {
+    // SAFETY: If the pointers point to invalid memory, UB will occur.
+    unsafe fn new(buffer: &'a [u8]) -> Self {
+        unsafe { Self::with_data(buffer, buffer) }
+    }
+
+    /// Like [`Self::new`] but the pointers of `entries` reference `data`
+    /// instead.
+    // SAFETY: If the pointers point to invalid memory, UB will occur.
+    unsafe fn with_data(entries: &'a [u8], data: &'a [u8]) -> Self {
+        Self {
+            buffer: data,
+            entries: unsafe { NullTermArrIter::new(entries) },
+        }
+    }
+}
+
+impl<'a> Iterator for CStrArrayIter<'a> {
+    type Item = &'a CStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+
+        // Assert in range
+        {
+            let end = &raw const self.buffer[self.buffer.len() - 1];
+            assert!(entry >= self.buffer.as_ptr());
+            assert!(entry <= end);
+        }
+
+        // offset of the pointer within the buffer
+        let begin_index = entry as usize - self.buffer.as_ptr() as usize;
+        let end_index_rel =
+            count_bytes_until_null(&self.buffer[begin_index..]).expect("should have NUL byte");
+        let end_index = begin_index + end_index_rel + 1 /* NUL byte */;
+        let cstr = CStr::from_bytes_with_nul(&self.buffer[begin_index..end_index]).unwrap();
+
+        Some(cstr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CStrArrayIter<'_> {}
+
+impl FusedIterator for CStrArrayIter<'_> {}
+
+/// Iterates over the `auxv` array with dynamic size until the end key is found.
+///
+/// Emits elements of type [`AuxVarRaw`].
+#[derive(Debug)]
+pub struct AuxVarRawIter<'a> {
+    // Buffer holds more bytes than necessary because the size of the auxv
+    // array is not known at compile time.
+    auxv: &'a [u8],
+    i: usize,
+    // Number of entries before the end key, determined by the initial scan.
+    len: usize,
+}
+
+impl<'a> AuxVarRawIter<'a> {
+    fn new(auxv: &'a [u8]) -> Self {
+        let len = (0..)
+            .map(|i| {
+                read_usize(auxv, i * size_of::<AuxVarRaw>())
+                    .filter(|_| (i + 1) * size_of::<AuxVarRaw>() <= auxv.len())
+                    .expect("auxv array ended prematurely")
+            })
+            // Stop at the end key. For invalid data, we also stop.
+            .take_while(
+                |&key| matches!(AuxVarType::try_from(key), Ok(key) if key != AuxVarType::Null),
+            )
+            .count();
+        Self { auxv, i: 0, len }
+    }
+}
+
+impl<'a> Iterator for AuxVarRawIter<'a> {
+    type Item = AuxVarRaw;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i == self.len {
+            return None;
+        }
+
+        let entry = unsafe {
+            let entry_ptr = self.auxv.as_ptr().cast::<AuxVarRaw>().add(self.i);
+            entry_ptr.as_ref().unwrap()
+        };
+
+        self.i += 1;
+        Some(*entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.i;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // The entries are in a contiguous array, so we can skip them directly.
+        self.i = self.i.saturating_add(n).min(self.len);
+        self.next()
+    }
+}
+
+impl ExactSizeIterator for AuxVarRawIter<'_> {}
+
+impl FusedIterator for AuxVarRawIter<'_> {}
+
+/// Iterates the [`AuxVar`]s of the stack layout.
+#[derive(Debug)]
+pub struct AuxVarIter<'a> {
+    // Buffer holds more bytes than necessary because the size of the auxv
+    // array is not known at compile time. The pointers must reference it.
+    auxv: &'a [u8],
+    serialized_iter: AuxVarRawIter<'a>,
+}
+
+impl<'a> AuxVarIter<'a> {
+    // SAFETY: If the pointers point to invalid memory, UB will occur.
+    unsafe fn new(auxv: &'a [u8]) -> Self {
+        unsafe { Self::with_data(auxv, auxv) }
+    }
+
+    /// Like [`Self::new`] but the pointers of `auxv` reference `data`
+    /// instead.
+    // SAFETY: If the pointers point to invalid memory, UB will occur.
+    unsafe fn with_data(auxv: &'a [u8], data: &'a [u8]) -> Self {
+        Self {
+            serialized_iter: AuxVarRawIter::new(auxv),
+            auxv: data,
+        }
+    }
+}
+
+impl<'a> Iterator for AuxVarIter<'a> {
+    type Item = AuxVar<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.serialized_iter
+                .next()
+                .map(|ref x| AuxVar::from_raw(x, self.auxv))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.serialized_iter.size_hint()
+    }
+}
+
+impl ExactSizeIterator for AuxVarIter<'_> {}
+
+impl FusedIterator for AuxVarIter<'_> {}
+
+/// The `auxv` entries of a stack layout, excluding the terminating
+/// [`AuxVarType::Null`] entry, as returned by [`StackLayoutRef::auxv_section`].
+///
+/// Iterating the section is safe, as only the raw [`AuxVarRaw`] values are
+/// emitted and no pointers are dereferenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuxvSection<'a> {
+    entries: &'a [AuxVarRaw],
+}
+
+impl<'a> AuxvSection<'a> {
+    /// Returns the number of entries.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the section has no entries.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries as slice.
+    #[must_use]
+    pub const fn as_slice(&self) -> &'a [AuxVarRaw] {
+        self.entries
+    }
+
+    /// Returns an iterator over the entries.
+    #[must_use]
+    pub fn iter(&self) -> AuxvIter<'a> {
+        AuxvIter {
+            entries: self.entries.iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for AuxvSection<'a> {
+    type Item = AuxVarRaw;
+    type IntoIter = AuxvIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &AuxvSection<'a> {
+    type Item = AuxVarRaw;
+    type IntoIter = AuxvIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterates the entries of an [`AuxvSection`].
+///
+/// Emits elements of type [`AuxVarRaw`].
+#[derive(Debug, Clone)]
+pub struct AuxvIter<'a> {
+    entries: core::slice::Iter<'a, AuxVarRaw>,
+}
+
+impl Iterator for AuxvIter<'_> {
+    type Item = AuxVarRaw;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().copied()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.entries.nth(n).copied()
+    }
+}
+
+impl DoubleEndedIterator for AuxvIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().copied()
+    }
+}
+
+impl ExactSizeIterator for AuxvIter<'_> {}
+
+impl FusedIterator for AuxvIter<'_> {}
+
+/// View of `argc` and `argv` of a stack layout, as returned by
+/// [`StackLayoutRef::split_at_envv`].
+#[derive(Debug, Clone, Copy)]
+pub struct ArgvView<'a> {
+    argc: usize,
+    // Starts at `argv` and ends at the end of the buffer.
+    argv: &'a [u8],
+}
+
+impl<'a> ArgvView<'a> {
+    /// Returns the number of arguments.
+    #[must_use]
+    pub const fn argc(&self) -> usize {
+        self.argc
+    }
+
+    /// Returns an iterator over the raw pointers of `argv`.
+    ///
+    /// See [`StackLayoutRef::argv_raw_iter`].
+    #[must_use]
+    pub fn raw_iter(&self) -> impl ExactSizeIterator<Item = *const u8> + FusedIterator {
+        unsafe { NullTermArrIter::new(self.argv) }
+    }
+
+    /// Returns an iterator over the arguments.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn iter(&self) -> impl ExactSizeIterator<Item = &'a CStr> + FusedIterator {
+        unsafe { CStrArrayIter::new(self.argv) }
+    }
+}
+
+/// View of `envv` and `auxv` of a stack layout, as returned by
+/// [`StackLayoutRef::split_at_envv`].
+#[derive(Debug, Clone, Copy)]
+pub struct EnvvAuxvView<'a> {
+    // Starts at `envv` and ends at the end of the buffer.
+    envv: &'a [u8],
+}
+
+impl<'a> EnvvAuxvView<'a> {
+    /// Returns the number of environment variables.
+    #[must_use]
+    pub fn envc(&self) -> usize {
+        self.envv_raw_iter().len()
+    }
+
+    /// Returns the number of `auxv` entries, excluding the terminating
+    /// [`AuxVarType::Null`] entry.
+    #[must_use]
+    pub fn auxvc(&self) -> usize {
+        self.auxv_raw_iter().len()
+    }
+
+    /// Returns an iterator over the raw pointers of `envv`.
+    ///
+    /// See [`StackLayoutRef::envv_raw_iter`].
+    #[must_use]
+    pub fn envv_raw_iter(&self) -> impl ExactSizeIterator<Item = *const u8> + FusedIterator {
+        unsafe { NullTermArrIter::new(self.envv) }
+    }
+
+    /// Returns an iterator over the raw `auxv` entries.
+    ///
+    /// See [`StackLayoutRef::auxv_raw_iter`].
+    #[must_use]
+    pub fn auxv_raw_iter(&self) -> impl ExactSizeIterator<Item = AuxVarRaw> + FusedIterator {
+        AuxVarRawIter::new(self.auxv_bytes())
+    }
+
+    /// Returns an iterator over the environment variables.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn envv_iter(&self) -> impl ExactSizeIterator<Item = &'a CStr> + FusedIterator {
+        unsafe { CStrArrayIter::new(self.envv) }
+    }
+
+    /// Returns an iterator over the [`AuxVar`]s.
+    ///
+    /// # Safety
+    /// The pointers must point to valid memory. If dereferenced, the memory
+    /// **must** be in the address space of the application. Otherwise,
+    /// segmentation faults or UB will occur.
+    #[must_use]
+    pub unsafe fn auxv_iter(&self) -> impl ExactSizeIterator<Item = AuxVar<'a>> + FusedIterator {
+        unsafe { AuxVarIter::new(self.auxv_bytes()) }
+    }
+
+    /// Returns the bytes from the start of `auxv` until the end of the buffer.
+    fn auxv_bytes(&self) -> &'a [u8] {
+        let start = (self.envc() + 1/* NULL */) * size_of::<usize>();
+        &self.envv[start..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
     use crate::StackLayoutRef;
 
-    #[repr(C, align(8))]
-    struct Aligned8<T>(T);
+    #[repr(C, align(8))]
+    struct Aligned8<T>(T);
+
+    impl<T> AsRef<T> for Aligned8<T> {
+        fn as_ref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    // Extracted from a Linux application during startup.
+    #[cfg(target_arch = "x86_64")]
+    const TEST_DATA_X86_64: Aligned8<[u8; 1592]> = Aligned8([
+        4, 0, 0, 0, 0, 0, 0, 0, 190, 252, 185, 93, 255, 127, 0, 0, 237, 252, 185, 93, 255, 127, 0,
+        0, 243, 252, 185, 93, 255, 127, 0, 0, 250, 252, 185, 93, 255, 127, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 253, 185, 93, 255, 127, 0, 0, 109, 253, 185, 93, 255, 127, 0, 0, 191, 253, 185,
+        93, 255, 127, 0, 0, 224, 253, 185, 93, 255, 127, 0, 0, 22, 254, 185, 93, 255, 127, 0, 0,
+        88, 254, 185, 93, 255, 127, 0, 0, 144, 254, 185, 93, 255, 127, 0, 0, 70, 0, 186, 93, 255,
+        127, 0, 0, 133, 0, 186, 93, 255, 127, 0, 0, 155, 0, 186, 93, 255, 127, 0, 0, 179, 0, 186,
+        93, 255, 127, 0, 0, 210, 0, 186, 93, 255, 127, 0, 0, 237, 0, 186, 93, 255, 127, 0, 0, 46,
+        1, 186, 93, 255, 127, 0, 0, 76, 1, 186, 93, 255, 127, 0, 0, 100, 1, 186, 93, 255, 127, 0,
+        0, 126, 1, 186, 93, 255, 127, 0, 0, 152, 1, 186, 93, 255, 127, 0, 0, 178, 1, 186, 93, 255,
+        127, 0, 0, 201, 1, 186, 93, 255, 127, 0, 0, 24, 2, 186, 93, 255, 127, 0, 0, 78, 2, 186, 93,
+        255, 127, 0, 0, 100, 2, 186, 93, 255, 127, 0, 0, 122, 2, 186, 93, 255, 127, 0, 0, 133, 2,
+        186, 93, 255, 127, 0, 0, 179, 2, 186, 93, 255, 127, 0, 0, 192, 2, 186, 93, 255, 127, 0, 0,
+        134, 15, 186, 93, 255, 127, 0, 0, 226, 15, 186, 93, 255, 127, 0, 0, 243, 15, 186, 93, 255,
+        127, 0, 0, 8, 16, 186, 93, 255, 127, 0, 0, 216, 16, 186, 93, 255, 127, 0, 0, 119, 18, 186,
+        93, 255, 127, 0, 0, 215, 18, 186, 93, 255, 127, 0, 0, 251, 18, 186, 93, 255, 127, 0, 0,
+        110, 29, 186, 93, 255, 127, 0, 0, 134, 29, 186, 93, 255, 127, 0, 0, 167, 29, 186, 93, 255,
+        127, 0, 0, 190, 29, 186, 93, 255, 127, 0, 0, 33, 31, 186, 93, 255, 127, 0, 0, 244, 33, 186,
+        93, 255, 127, 0, 0, 8, 34, 186, 93, 255, 127, 0, 0, 189, 35, 186, 93, 255, 127, 0, 0, 236,
+        35, 186, 93, 255, 127, 0, 0, 81, 36, 186, 93, 255, 127, 0, 0, 181, 36, 186, 93, 255, 127,
+        0, 0, 37, 37, 186, 93, 255, 127, 0, 0, 60, 37, 186, 93, 255, 127, 0, 0, 77, 37, 186, 93,
+        255, 127, 0, 0, 100, 37, 186, 93, 255, 127, 0, 0, 130, 37, 186, 93, 255, 127, 0, 0, 157,
+        37, 186, 93, 255, 127, 0, 0, 181, 37, 186, 93, 255, 127, 0, 0, 201, 37, 186, 93, 255, 127,
+        0, 0, 224, 37, 186, 93, 255, 127, 0, 0, 245, 37, 186, 93, 255, 127, 0, 0, 14, 38, 186, 93,
+        255, 127, 0, 0, 34, 38, 186, 93, 255, 127, 0, 0, 194, 39, 186, 93, 255, 127, 0, 0, 227, 39,
+        186, 93, 255, 127, 0, 0, 43, 40, 186, 93, 255, 127, 0, 0, 14, 41, 186, 93, 255, 127, 0, 0,
+        78, 41, 186, 93, 255, 127, 0, 0, 190, 41, 186, 93, 255, 127, 0, 0, 207, 41, 186, 93, 255,
+        127, 0, 0, 239, 41, 186, 93, 255, 127, 0, 0, 108, 49, 186, 93, 255, 127, 0, 0, 124, 49,
+        186, 93, 255, 127, 0, 0, 12, 50, 186, 93, 255, 127, 0, 0, 63, 50, 186, 93, 255, 127, 0, 0,
+        81, 50, 186, 93, 255, 127, 0, 0, 188, 50, 186, 93, 255, 127, 0, 0, 231, 50, 186, 93, 255,
+        127, 0, 0, 140, 51, 186, 93, 255, 127, 0, 0, 193, 51, 186, 93, 255, 127, 0, 0, 253, 51,
+        186, 93, 255, 127, 0, 0, 133, 52, 186, 93, 255, 127, 0, 0, 56, 53, 186, 93, 255, 127, 0, 0,
+        117, 53, 186, 93, 255, 127, 0, 0, 200, 53, 186, 93, 255, 127, 0, 0, 242, 53, 186, 93, 255,
+        127, 0, 0, 253, 53, 186, 93, 255, 127, 0, 0, 210, 56, 186, 93, 255, 127, 0, 0, 220, 56,
+        186, 93, 255, 127, 0, 0, 3, 57, 186, 93, 255, 127, 0, 0, 60, 58, 186, 93, 255, 127, 0, 0,
+        165, 58, 186, 93, 255, 127, 0, 0, 187, 58, 186, 93, 255, 127, 0, 0, 222, 58, 186, 93, 255,
+        127, 0, 0, 15, 59, 186, 93, 255, 127, 0, 0, 38, 59, 186, 93, 255, 127, 0, 0, 120, 59, 186,
+        93, 255, 127, 0, 0, 157, 59, 186, 93, 255, 127, 0, 0, 165, 59, 186, 93, 255, 127, 0, 0, 10,
+        60, 186, 93, 255, 127, 0, 0, 51, 60, 186, 93, 255, 127, 0, 0, 83, 60, 186, 93, 255, 127, 0,
+        0, 130, 60, 186, 93, 255, 127, 0, 0, 152, 60, 186, 93, 255, 127, 0, 0, 172, 60, 186, 93,
+        255, 127, 0, 0, 209, 60, 186, 93, 255, 127, 0, 0, 223, 61, 186, 93, 255, 127, 0, 0, 20, 62,
+        186, 93, 255, 127, 0, 0, 47, 62, 186, 93, 255, 127, 0, 0, 67, 62, 186, 93, 255, 127, 0, 0,
+        81, 62, 186, 93, 255, 127, 0, 0, 99, 62, 186, 93, 255, 127, 0, 0, 112, 62, 186, 93, 255,
+        127, 0, 0, 138, 62, 186, 93, 255, 127, 0, 0, 192, 62, 186, 93, 255, 127, 0, 0, 237, 64,
+        186, 93, 255, 127, 0, 0, 43, 66, 186, 93, 255, 127, 0, 0, 69, 66, 186, 93, 255, 127, 0, 0,
+        29, 76, 186, 93, 255, 127, 0, 0, 52, 76, 186, 93, 255, 127, 0, 0, 83, 76, 186, 93, 255,
+        127, 0, 0, 106, 76, 186, 93, 255, 127, 0, 0, 132, 76, 186, 93, 255, 127, 0, 0, 157, 76,
+        186, 93, 255, 127, 0, 0, 193, 76, 186, 93, 255, 127, 0, 0, 237, 76, 186, 93, 255, 127, 0,
+        0, 30, 77, 186, 93, 255, 127, 0, 0, 76, 77, 186, 93, 255, 127, 0, 0, 109, 77, 186, 93, 255,
+        127, 0, 0, 159, 77, 186, 93, 255, 127, 0, 0, 180, 77, 186, 93, 255, 127, 0, 0, 220, 77,
+        186, 93, 255, 127, 0, 0, 43, 78, 186, 93, 255, 127, 0, 0, 60, 78, 186, 93, 255, 127, 0, 0,
+        81, 78, 186, 93, 255, 127, 0, 0, 115, 78, 186, 93, 255, 127, 0, 0, 211, 78, 186, 93, 255,
+        127, 0, 0, 236, 78, 186, 93, 255, 127, 0, 0, 18, 79, 186, 93, 255, 127, 0, 0, 53, 79, 186,
+        93, 255, 127, 0, 0, 95, 79, 186, 93, 255, 127, 0, 0, 116, 79, 186, 93, 255, 127, 0, 0, 141,
+        79, 186, 93, 255, 127, 0, 0, 170, 79, 186, 93, 255, 127, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 33,
+        0, 0, 0, 0, 0, 0, 0, 0, 208, 160, 43, 202, 127, 0, 0, 51, 0, 0, 0, 0, 0, 0, 0, 48, 14, 0,
+        0, 0, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0, 255, 251, 235, 191, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0,
+        0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 17, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0,
+        0, 0, 0, 0, 0, 64, 144, 123, 202, 20, 86, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 56, 0, 0, 0, 0, 0,
+        0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 240, 160,
+        43, 202, 127, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0,
+        64, 25, 124, 202, 20, 86, 0, 0, 11, 0, 0, 0, 0, 0, 0, 0, 232, 3, 0, 0, 0, 0, 0, 0, 12, 0,
+        0, 0, 0, 0, 0, 0, 232, 3, 0, 0, 0, 0, 0, 0, 13, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0,
+        0, 14, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, 23, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 25, 0, 0, 0, 0, 0, 0, 0, 153, 240, 185, 93, 255, 127, 0, 0, 26, 0, 0, 0, 0, 0,
+        0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 31, 0, 0, 0, 0, 0, 0, 0, 201, 79, 186, 93, 255, 127, 0, 0,
+        15, 0, 0, 0, 0, 0, 0, 0, 169, 240, 185, 93, 255, 127, 0, 0, 27, 0, 0, 0, 0, 0, 0, 0, 28, 0,
+        0, 0, 0, 0, 0, 0, 28, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 253, 120, 161, 11, 82, 13, 91, 238, 102,
+        222, 133, 171, 66, 146, 247, 165, 120, 56, 54, 95, 54, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ]);
+
+    // Extracted from a Linux application during startup.
+    #[cfg(target_arch = "x86")]
+    const TEST_DATA_X86: Aligned8<[u8; 804]> = Aligned8([
+        4, 0, 0, 0, 148, 92, 235, 255, 213, 92, 235, 255, 219, 92, 235, 255, 226, 92, 235, 255, 0,
+        0, 0, 0, 232, 92, 235, 255, 85, 93, 235, 255, 167, 93, 235, 255, 200, 93, 235, 255, 254,
+        93, 235, 255, 64, 94, 235, 255, 120, 94, 235, 255, 30, 95, 235, 255, 93, 95, 235, 255, 115,
+        95, 235, 255, 139, 95, 235, 255, 170, 95, 235, 255, 197, 95, 235, 255, 6, 96, 235, 255, 36,
+        96, 235, 255, 60, 96, 235, 255, 86, 96, 235, 255, 112, 96, 235, 255, 138, 96, 235, 255,
+        161, 96, 235, 255, 240, 96, 235, 255, 38, 97, 235, 255, 60, 97, 235, 255, 82, 97, 235, 255,
+        93, 97, 235, 255, 139, 97, 235, 255, 152, 97, 235, 255, 94, 110, 235, 255, 186, 110, 235,
+        255, 203, 110, 235, 255, 224, 110, 235, 255, 176, 111, 235, 255, 55, 114, 235, 255, 151,
+        114, 235, 255, 186, 114, 235, 255, 45, 125, 235, 255, 69, 125, 235, 255, 102, 125, 235,
+        255, 125, 125, 235, 255, 224, 126, 235, 255, 179, 129, 235, 255, 199, 129, 235, 255, 124,
+        131, 235, 255, 171, 131, 235, 255, 16, 132, 235, 255, 116, 132, 235, 255, 228, 132, 235,
+        255, 251, 132, 235, 255, 12, 133, 235, 255, 35, 133, 235, 255, 65, 133, 235, 255, 92, 133,
+        235, 255, 116, 133, 235, 255, 136, 133, 235, 255, 159, 133, 235, 255, 180, 133, 235, 255,
+        205, 133, 235, 255, 225, 133, 235, 255, 176, 135, 235, 255, 209, 135, 235, 255, 25, 136,
+        235, 255, 252, 136, 235, 255, 60, 137, 235, 255, 172, 137, 235, 255, 189, 137, 235, 255,
+        221, 137, 235, 255, 90, 145, 235, 255, 106, 145, 235, 255, 250, 145, 235, 255, 45, 146,
+        235, 255, 63, 146, 235, 255, 170, 146, 235, 255, 213, 146, 235, 255, 122, 147, 235, 255,
+        175, 147, 235, 255, 235, 147, 235, 255, 115, 148, 235, 255, 38, 149, 235, 255, 99, 149,
+        235, 255, 182, 149, 235, 255, 224, 149, 235, 255, 235, 149, 235, 255, 192, 152, 235, 255,
+        202, 152, 235, 255, 241, 152, 235, 255, 42, 154, 235, 255, 147, 154, 235, 255, 169, 154,
+        235, 255, 204, 154, 235, 255, 253, 154, 235, 255, 20, 155, 235, 255, 102, 155, 235, 255,
+        139, 155, 235, 255, 147, 155, 235, 255, 248, 155, 235, 255, 33, 156, 235, 255, 65, 156,
+        235, 255, 112, 156, 235, 255, 134, 156, 235, 255, 154, 156, 235, 255, 191, 156, 235, 255,
+        205, 157, 235, 255, 2, 158, 235, 255, 29, 158, 235, 255, 49, 158, 235, 255, 63, 158, 235,
+        255, 81, 158, 235, 255, 94, 158, 235, 255, 120, 158, 235, 255, 174, 158, 235, 255, 219,
+        160, 235, 255, 25, 162, 235, 255, 51, 162, 235, 255, 11, 172, 235, 255, 34, 172, 235, 255,
+        65, 172, 235, 255, 88, 172, 235, 255, 114, 172, 235, 255, 139, 172, 235, 255, 175, 172,
+        235, 255, 219, 172, 235, 255, 12, 173, 235, 255, 58, 173, 235, 255, 91, 173, 235, 255, 141,
+        173, 235, 255, 162, 173, 235, 255, 202, 173, 235, 255, 25, 174, 235, 255, 42, 174, 235,
+        255, 63, 174, 235, 255, 97, 174, 235, 255, 193, 174, 235, 255, 218, 174, 235, 255, 0, 175,
+        235, 255, 35, 175, 235, 255, 77, 175, 235, 255, 98, 175, 235, 255, 123, 175, 235, 255, 152,
+        175, 235, 255, 0, 0, 0, 0, 32, 0, 0, 0, 176, 117, 245, 247, 33, 0, 0, 0, 0, 112, 245, 247,
+        51, 0, 0, 0, 48, 14, 0, 0, 16, 0, 0, 0, 255, 251, 235, 191, 6, 0, 0, 0, 0, 16, 0, 0, 17, 0,
+        0, 0, 100, 0, 0, 0, 3, 0, 0, 0, 52, 128, 4, 8, 4, 0, 0, 0, 32, 0, 0, 0, 5, 0, 0, 0, 8, 0,
+        0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 201, 170, 4, 8, 11, 0, 0,
+        0, 232, 3, 0, 0, 12, 0, 0, 0, 232, 3, 0, 0, 13, 0, 0, 0, 100, 0, 0, 0, 14, 0, 0, 0, 100, 0,
+        0, 0, 23, 0, 0, 0, 0, 0, 0, 0, 25, 0, 0, 0, 219, 77, 235, 255, 26, 0, 0, 0, 2, 0, 0, 0, 31,
+        0, 0, 0, 183, 175, 235, 255, 15, 0, 0, 0, 235, 77, 235, 255, 27, 0, 0, 0, 28, 0, 0, 0, 28,
+        0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 213, 250, 168, 134, 233, 229,
+        101, 88, 100, 213, 132, 214, 57, 104, 200, 105, 54, 56, 54, 0, 0, 0, 0, 0,
+    ]);
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_parse_real_data() {
+        let data = TEST_DATA_X86_64.as_ref();
+        let layout = StackLayoutRef::new(data, None);
+        assert!(StackLayoutRef::try_new(data, None).is_ok());
+
+        assert_eq!(layout.argc(), 4);
+
+        // argv
+        {
+            assert_eq!(
+                layout
+                    .get_slice_argv()
+                    .as_ptr()
+                    .align_offset(align_of::<usize>()),
+                0
+            );
+            assert_eq!(layout.argv_raw_iter().count(), 4);
+            // Just printing uncovers memory errors
+            layout
+                .argv_raw_iter()
+                .enumerate()
+                .for_each(|(i, ptr)| eprintln!("  arg {i:>2}: {ptr:?}"));
+        }
+
+        // envv
+        {
+            assert_eq!(
+                layout
+                    .get_slice_envv()
+                    .as_ptr()
+                    .align_offset(align_of::<usize>()),
+                0
+            );
+            assert_eq!(layout.envv_raw_iter().count(), 139);
+            // Just printing uncovers memory errors
+            layout
+                .envv_raw_iter()
+                .enumerate()
+                .for_each(|(i, ptr)| eprintln!("  env {i:>2}: {ptr:?}"));
+        }
+
+        // auxv
+        {
+            assert_eq!(
+                layout
+                    .get_slice_auxv()
+                    .as_ptr()
+                    .align_offset(align_of::<usize>()),
+                0
+            );
+            // Just printing uncovers memory errors
+            assert_eq!(layout.auxv_raw_iter().count(), 20);
+            layout
+                .auxv_raw_iter()
+                .enumerate()
+                .for_each(|(i, ptr)| eprintln!("  aux {i:>2}: {ptr:?}"));
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86")]
+    fn test_parse_real_data() {
+        let data = TEST_DATA_X86.as_ref();
+        let layout = StackLayoutRef::new(data, None);
+        assert!(StackLayoutRef::try_new(data, None).is_ok());
+
+        assert_eq!(layout.argc(), 4);
+
+        // argv
+        {
+            assert_eq!(
+                layout
+                    .get_slice_argv()
+                    .as_ptr()
+                    .align_offset(align_of::<usize>()),
+                0
+            );
+
+            // Just printing uncovers memory errors
+            layout
+                .argv_raw_iter()
+                .enumerate()
+                .for_each(|(i, ptr)| eprintln!("  arg {i:>2}: {ptr:?}"));
+
+            assert_eq!(layout.argv_raw_iter().count(), 4);
+        }
+
+        // envv
+        {
+            assert_eq!(
+                layout
+                    .get_slice_envv()
+                    .as_ptr()
+                    .align_offset(align_of::<usize>()),
+                0
+            );
+
+            // Just printing uncovers memory errors
+            layout
+                .envv_raw_iter()
+                .enumerate()
+                .for_each(|(i, ptr)| eprintln!("  env {i:>2}: {ptr:?}"));
 
-    impl<T> AsRef<T> for Aligned8<T> {
-        fn as_ref(&self) -> &T {
-            &self.0
+            assert_eq!(layout.envv_raw_iter().count(), 139);
+        }
+
+        // auxv
+        {
+            assert_eq!(
+                layout
+                    .get_slice_auxv()
+                    .as_ptr()
+                    .align_offset(align_of::<usize>()),
+                0
+            );
+
+            // Just printing uncovers memory errors
+            layout
+                .auxv_raw_iter()
+                .enumerate()
+                .for_each(|(i, ptr)| eprintln!("  aux {i:>2}: {ptr:?}"));
+            assert_eq!(layout.auxv_raw_iter().count(), 21);
         }
     }
 
-    // Extracted from a Linux application during startup.
-    #[cfg(target_arch = "x86_64")]
-    const TEST_DATA_X86_64: Aligned8<[u8; 1592]> = Aligned8([
-        4, 0, 0, 0, 0, 0, 0, 0, 190, 252, 185, 93, 255, 127, 0, 0, 237, 252, 185, 93, 255, 127, 0,
-        0, 243, 252, 185, 93, 255, 127, 0, 0, 250, 252, 185, 93, 255, 127, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 0, 0, 253, 185, 93, 255, 127, 0, 0, 109, 253, 185, 93, 255, 127, 0, 0, 191, 253, 185,
-        93, 255, 127, 0, 0, 224, 253, 185, 93, 255, 127, 0, 0, 22, 254, 185, 93, 255, 127, 0, 0,
-        88, 254, 185, 93, 255, 127, 0, 0, 144, 254, 185, 93, 255, 127, 0, 0, 70, 0, 186, 93, 255,
-        127, 0, 0, 133, 0, 186, 93, 255, 127, 0, 0, 155, 0, 186, 93, 255, 127, 0, 0, 179, 0, 186,
-        93, 255, 127, 0, 0, 210, 0, 186, 93, 255, 127, 0, 0, 237, 0, 186, 93, 255, 127, 0, 0, 46,
-        1, 186, 93, 255, 127, 0, 0, 76, 1, 186, 93, 255, 127, 0, 0, 100, 1, 186, 93, 255, 127, 0,
-        0, 126, 1, 186, 93, 255, 127, 0, 0, 152, 1, 186, 93, 255, 127, 0, 0, 178, 1, 186, 93, 255,
-        127, 0, 0, 201, 1, 186, 93, 255, 127, 0, 0, 24, 2, 186, 93, 255, 127, 0, 0, 78, 2, 186, 93,
-        255, 127, 0, 0, 100, 2, 186, 93, 255, 127, 0, 0, 122, 2, 186, 93, 255, 127, 0, 0, 133, 2,
-        186, 93, 255, 127, 0, 0, 179, 2, 186, 93, 255, 127, 0, 0, 192, 2, 186, 93, 255, 127, 0, 0,
-        134, 15, 186, 93, 255, 127, 0, 0, 226, 15, 186, 93, 255, 127, 0, 0, 243, 15, 186, 93, 255,
-        127, 0, 0, 8, 16, 186, 93, 255, 127, 0, 0, 216, 16, 186, 93, 255, 127, 0, 0, 119, 18, 186,
-        93, 255, 127, 0, 0, 215, 18, 186, 93, 255, 127, 0, 0, 251, 18, 186, 93, 255, 127, 0, 0,
-        110, 29, 186, 93, 255, 127, 0, 0, 134, 29, 186, 93, 255, 127, 0, 0, 167, 29, 186, 93, 255,
-        127, 0, 0, 190, 29, 186, 93, 255, 127, 0, 0, 33, 31, 186, 93, 255, 127, 0, 0, 244, 33, 186,
-        93, 255, 127, 0, 0, 8, 34, 186, 93, 255, 127, 0, 0, 189, 35, 186, 93, 255, 127, 0, 0, 236,
-        35, 186, 93, 255, 127, 0, 0, 81, 36, 186, 93, 255, 127, 0, 0, 181, 36, 186, 93, 255, 127,
-        0, 0, 37, 37, 186, 93, 255, 127, 0, 0, 60, 37, 186, 93, 255, 127, 0, 0, 77, 37, 186, 93,
-        255, 127, 0, 0, 100, 37, 186, 93, 255, 127, 0, 0, 130, 37, 186, 93, 255, 127, 0, 0, 157,
-        37, 186, 93, 255, 127, 0, 0, 181, 37, 186, 93, 255, 127, 0, 0, 201, 37, 186, 93, 255, 127,
-        0, 0, 224, 37, 186, 93, 255, 127, 0, 0, 245, 37, 186, 93, 255, 127, 0, 0, 14, 38, 186, 93,
-        255, 127, 0, 0, 34, 38, 186, 93, 255, 127, 0, 0, 194, 39, 186, 93, 255, 127, 0, 0, 227, 39,
-        186, 93, 255, 127, 0, 0, 43, 40, 186, 93, 255, 127, 0, 0, 14, 41, 186, 93, 255, 127, 0, 0,
-        78, 41, 186, 93, 255, 127, 0, 0, 190, 41, 186, 93, 255, 127, 0, 0, 207, 41, 186, 93, 255,
-        127, 0, 0, 239, 41, 186, 93, 255, 127, 0, 0, 108, 49, 186, 93, 255, 127, 0, 0, 124, 49,
-        186, 93, 255, 127, 0, 0, 12, 50, 186, 93, 255, 127, 0, 0, 63, 50, 186, 93, 255, 127, 0, 0,
-        81, 50, 186, 93, 255, 127, 0, 0, 188, 50, 186, 93, 255, 127, 0, 0, 231, 50, 186, 93, 255,
-        127, 0, 0, 140, 51, 186, 93, 255, 127, 0, 0, 193, 51, 186, 93, 255, 127, 0, 0, 253, 51,
-        186, 93, 255, 127, 0, 0, 133, 52, 186, 93, 255, 127, 0, 0, 56, 53, 186, 93, 255, 127, 0, 0,
-        117, 53, 186, 93, 255, 127, 0, 0, 200, 53, 186, 93, 255, 127, 0, 0, 242, 53, 186, 93, 255,
-        127, 0, 0, 253, 53, 186, 93, 255, 127, 0, 0, 210, 56, 186, 93, 255, 127, 0, 0, 220, 56,
-        186, 93, 255, 127, 0, 0, 3, 57, 186, 93, 255, 127, 0, 0, 60, 58, 186, 93, 255, 127, 0, 0,
-        165, 58, 186, 93, 255, 127, 0, 0, 187, 58, 186, 93, 255, 127, 0, 0, 222, 58, 186, 93, 255,
-        127, 0, 0, 15, 59, 186, 93, 255, 127, 0, 0, 38, 59, 186, 93, 255, 127, 0, 0, 120, 59, 186,
-        93, 255, 127, 0, 0, 157, 59, 186, 93, 255, 127, 0, 0, 165, 59, 186, 93, 255, 127, 0, 0, 10,
-        60, 186, 93, 255, 127, 0, 0, 51, 60, 186, 93, 255, 127, 0, 0, 83, 60, 186, 93, 255, 127, 0,
-        0, 130, 60, 186, 93, 255, 127, 0, 0, 152, 60, 186, 93, 255, 127, 0, 0, 172, 60, 186, 93,
-        255, 127, 0, 0, 209, 60, 186, 93, 255, 127, 0, 0, 223, 61, 186, 93, 255, 127, 0, 0, 20, 62,
-        186, 93, 255, 127, 0, 0, 47, 62, 186, 93, 255, 127, 0, 0, 67, 62, 186, 93, 255, 127, 0, 0,
-        81, 62, 186, 93, 255, 127, 0, 0, 99, 62, 186, 93, 255, 127, 0, 0, 112, 62, 186, 93, 255,
-        127, 0, 0, 138, 62, 186, 93, 255, 127, 0, 0, 192, 62, 186, 93, 255, 127, 0, 0, 237, 64,
-        186, 93, 255, 127, 0, 0, 43, 66, 186, 93, 255, 127, 0, 0, 69, 66, 186, 93, 255, 127, 0, 0,
-        29, 76, 186, 93, 255, 127, 0, 0, 52, 76, 186, 93, 255, 127, 0, 0, 83, 76, 186, 93, 255,
-        127, 0, 0, 106, 76, 186, 93, 255, 127, 0, 0, 132, 76, 186, 93, 255, 127, 0, 0, 157, 76,
-        186, 93, 255, 127, 0, 0, 193, 76, 186, 93, 255, 127, 0, 0, 237, 76, 186, 93, 255, 127, 0,
-        0, 30, 77, 186, 93, 255, 127, 0, 0, 76, 77, 186, 93, 255, 127, 0, 0, 109, 77, 186, 93, 255,
-        127, 0, 0, 159, 77, 186, 93, 255, 127, 0, 0, 180, 77, 186, 93, 255, 127, 0, 0, 220, 77,
-        186, 93, 255, 127, 0, 0, 43, 78, 186, 93, 255, 127, 0, 0, 60, 78, 186, 93, 255, 127, 0, 0,
-        81, 78, 186, 93, 255, 127, 0, 0, 115, 78, 186, 93, 255, 127, 0, 0, 211, 78, 186, 93, 255,
-        127, 0, 0, 236, 78, 186, 93, 255, 127, 0, 0, 18, 79, 186, 93, 255, 127, 0, 0, 53, 79, 186,
-        93, 255, 127, 0, 0, 95, 79, 186, 93, 255, 127, 0, 0, 116, 79, 186, 93, 255, 127, 0, 0, 141,
-        79, 186, 93, 255, 127, 0, 0, 170, 79, 186, 93, 255, 127, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 33,
-        0, 0, 0, 0, 0, 0, 0, 0, 208, 160, 43, 202, 127, 0, 0, 51, 0, 0, 0, 0, 0, 0, 0, 48, 14, 0,
-        0, 0, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0, 255, 251, 235, 191, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0,
-        0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 17, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0,
-        0, 0, 0, 0, 0, 64, 144, 123, 202, 20, 86, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 56, 0, 0, 0, 0, 0,
-        0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 240, 160,
-        43, 202, 127, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0,
-        64, 25, 124, 202, 20, 86, 0, 0, 11, 0, 0, 0, 0, 0, 0, 0, 232, 3, 0, 0, 0, 0, 0, 0, 12, 0,
-        0, 0, 0, 0, 0, 0, 232, 3, 0, 0, 0, 0, 0, 0, 13, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0,
-        0, 14, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, 23, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 0, 0, 0, 25, 0, 0, 0, 0, 0, 0, 0, 153, 240, 185, 93, 255, 127, 0, 0, 26, 0, 0, 0, 0, 0,
-        0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 31, 0, 0, 0, 0, 0, 0, 0, 201, 79, 186, 93, 255, 127, 0, 0,
-        15, 0, 0, 0, 0, 0, 0, 0, 169, 240, 185, 93, 255, 127, 0, 0, 27, 0, 0, 0, 0, 0, 0, 0, 28, 0,
-        0, 0, 0, 0, 0, 0, 28, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 253, 120, 161, 11, 82, 13, 91, 238, 102,
-        222, 133, 171, 66, 146, 247, 165, 120, 56, 54, 95, 54, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-    ]);
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_new_argc32() {
+        // The upper half of the `argc` word contains garbage.
+        let argc = if cfg!(target_endian = "little") {
+            0xdead_beef_0000_0002
+        } else {
+            0x0000_0002_dead_beef
+        };
+        let words = [argc, 0x1000, 0x2000, 0, 0, 0, 0];
+        let bytes = StackLayoutRef::from_words(&words).as_bytes();
+        let layout = StackLayoutRef::new_argc32(bytes);
+        assert_eq!(layout.argc(), 2);
+        assert!(layout.argv_raw_iter().eq([0x1000 as _, 0x2000 as _]));
+        assert_eq!(layout.envc(), 0);
+        assert_eq!(layout.auxvc(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_new_strings_first() {
+        use crate::{AuxVar, LayoutFormat, StackLayoutBuilder};
+        use aligned_vec::AVec;
+        use std::vec;
+        use std::vec::Vec;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.extend_argv(["./prog", "--flag"]);
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::platform("x86_64"));
+
+        // data areas first, padded to a word boundary, then the entries
+        let build_strings_first = |builder: StackLayoutBuilder| {
+            let (entries, data) = builder.clone().build_no_data_area(0);
+            let data_len = data.len().next_multiple_of(size_of::<usize>());
+            let mut buffer =
+                AVec::<u8>::from_slice(align_of::<usize>(), &vec![0; data_len + entries.len()]);
+            let (entries, data) = builder.build_no_data_area(buffer.as_ptr() as usize);
+            buffer[..data.len()].copy_from_slice(&data);
+            buffer[data_len..].copy_from_slice(&entries);
+            (buffer, data_len)
+        };
+        let (buffer, data_len) = build_strings_first(builder);
+
+        let layout = StackLayoutRef::new_strings_first(&buffer).unwrap();
+        assert_eq!(
+            layout.as_ptr() as usize,
+            buffer.as_ptr() as usize + data_len
+        );
+        assert_eq!(layout.layout_format(), LayoutFormat::DataAreaFirst);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter().eq([c"./prog", c"--flag"]));
+            assert!(layout.envv_iter().eq([c"FOO=bar"]));
+            let auxv = layout.auxv_iter().collect::<Vec<_>>();
+            assert_eq!(auxv[0], AuxVar::Pagesz(4096));
+            assert_eq!(auxv[1], AuxVar::Platform(c"x86_64".into()));
+        }
+
+        // without arguments and environment variables
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::platform("x86_64"));
+        let (buffer, data_len) = build_strings_first(builder);
+        let layout = StackLayoutRef::new_strings_first(&buffer).unwrap();
+        assert_eq!(
+            layout.as_ptr() as usize,
+            buffer.as_ptr() as usize + data_len
+        );
+        assert_eq!(layout.argc(), 0);
+        assert_eq!(layout.envc(), 0);
+        assert_eq!(layout.auxvc(), 1);
+
+        // a standard layout doesn't fit the heuristic
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        let buffer = builder.build();
+        assert!(StackLayoutRef::new_strings_first(&buffer).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "entry >= self.buffer.as_ptr()")]
+    fn test_argv_iter_pointer_before_buffer() {
+        let mut words = [0_usize; 8];
+        // The layout starts at the third word and `argv[0]` points to the
+        // empty string in the first word, i.e., before the layout.
+        words[2] = 1;
+        words[3] = words.as_ptr() as usize;
+        let layout = StackLayoutRef::from_words(&words[2..]);
+        // SAFETY: The pointer is valid, but outside of the layout, which is
+        // asserted.
+        let _ = unsafe { layout.argv_iter() }.next();
+    }
+
+    #[test]
+    fn test_truncate() {
+        use super::StackLayoutError;
+
+        let words = [2, 0x1000, 0x2000, 0, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert_eq!(layout.truncate(3), &layout.as_bytes()[..3]);
+        assert_eq!(layout.truncate(usize::MAX), layout.as_bytes());
+
+        let bytes = layout.truncate_at_argv(1);
+        assert_eq!(bytes.len(), 2 * size_of::<usize>());
+        assert_eq!(
+            StackLayoutRef::try_new(bytes, None).unwrap_err(),
+            StackLayoutError::UnterminatedArgv
+        );
+        assert_eq!(layout.truncate_at_argv(usize::MAX), layout.as_bytes());
+
+        let layout = StackLayoutRef::new(&layout.as_bytes()[size_of::<usize>()..], Some(2));
+        assert_eq!(layout.truncate_at_argv(2).len(), 2 * size_of::<usize>());
+    }
+
+    #[test]
+    fn test_try_new() {
+        use super::StackLayoutError;
+
+        let parse = |words: &[usize]| {
+            // SAFETY: Any initialized memory is a valid byte slice.
+            let bytes = unsafe {
+                core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), size_of_val(words))
+            };
+            StackLayoutRef::try_new(bytes, None).map(|layout| {
+                (
+                    layout.argc(),
+                    layout.argv_raw_iter().count(),
+                    layout.envc(),
+                    layout.auxvc(),
+                )
+            })
+        };
+
+        assert_eq!(parse(&[]), Err(StackLayoutError::MissingArgc));
+        assert_eq!(parse(&[1]), Err(StackLayoutError::UnterminatedArgv));
+        assert_eq!(
+            parse(&[usize::MAX]),
+            Err(StackLayoutError::UnterminatedArgv)
+        );
+        assert_eq!(
+            parse(&[1, 0x1000, 0x1000]),
+            Err(StackLayoutError::ArgcMismatch)
+        );
+        assert_eq!(
+            parse(&[1, 0x1000, 0, 0x2000]),
+            Err(StackLayoutError::UnterminatedEnvv)
+        );
+        assert_eq!(
+            parse(&[1, 0x1000, 0, 0x2000, 0, 6]),
+            Err(StackLayoutError::UnterminatedAuxv)
+        );
+        assert_eq!(
+            parse(&[1, 0x1000, 0, 0x2000, 0, 6, 4096, 0, 0]),
+            Ok((1, 1, 1, 1))
+        );
+    }
+
+    /// Tests that the `auxv` entries are emitted in the order the kernel
+    /// provided them.
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore = "requires file system access")]
+    fn test_auxv_order_of_real_kernel() {
+        use crate::AuxVarType;
+        use std::vec::Vec;
+
+        let auxv = std::fs::read("/proc/self/auxv").unwrap();
+        let keys = auxv
+            .chunks_exact(2 * size_of::<usize>())
+            .map(|entry| usize::from_ne_bytes(entry[..size_of::<usize>()].try_into().unwrap()))
+            .map_while(|key| AuxVarType::try_from(key).ok())
+            .take_while(|&key| key != AuxVarType::Null)
+            .collect::<Vec<_>>();
+        assert!(!keys.is_empty());
+
+        // empty argv and envv followed by the auxv of the kernel
+        let words = [0_usize; 3]
+            .into_iter()
+            .chain(
+                auxv.chunks_exact(size_of::<usize>())
+                    .map(|word| usize::from_ne_bytes(word.try_into().unwrap())),
+            )
+            .collect::<Vec<_>>();
+        let layout = StackLayoutRef::from_words(&words);
+
+        let parsed_keys = layout
+            .auxv_raw_iter()
+            .map(|raw| raw.key().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parsed_keys, keys);
+        for (i, &key) in keys.iter().enumerate() {
+            assert_eq!(
+                layout.auxv_index_of(key),
+                keys.iter().position(|&k| k == key)
+            );
+            assert!(layout.auxv_index_of(key).unwrap() <= i);
+        }
+        assert_eq!(layout.auxv_index_of(AuxVarType::Null), None);
+    }
+
+    #[test]
+    fn test_raw_iter_including_null() {
+        use std::vec::Vec;
+
+        let words = [2, 0x1000, 0x2000, 0, 0x3000, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let argv = layout.argv_raw_iter_including_null().collect::<Vec<_>>();
+        assert_eq!(argv, [Some(0x1000 as _), Some(0x2000 as _), None]);
+        let envv = layout.envv_raw_iter_including_null().collect::<Vec<_>>();
+        assert_eq!(envv, [Some(0x3000 as _), None]);
+    }
+
+    #[test]
+    fn test_raw_iter_fused() {
+        use std::vec::Vec;
+
+        // Garbage after the null entry must never be emitted.
+        let words = [2, 0x1000, 0x2000, 0, 0xdead, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let mut iter = layout.argv_raw_iter();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.next(), Some(0x1000 as _));
+        assert_eq!(iter.size_hint(), (1, Some(1)));
+        assert_eq!(iter.next(), Some(0x2000 as _));
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        let argv = layout.argv_raw_iter().fuse().collect::<Vec<_>>();
+        assert_eq!(argv, [0x1000 as _, 0x2000 as _]);
+        assert_eq!(layout.envv_raw_iter().len(), 1);
+    }
+
+    #[test]
+    fn test_nth_raw() {
+        use crate::{AuxVarRaw, AuxVarType};
+
+        let words = [2, 0x1000, 0x2000, 0, 0x3000, 0, 6, 4096, 11, 1000, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert_eq!(layout.nth_argv_raw(0), Some(0x1000));
+        assert_eq!(layout.nth_argv_raw(1), Some(0x2000));
+        assert_eq!(layout.nth_argv_raw(2), None);
+        assert_eq!(layout.nth_envv_raw(0), Some(0x3000));
+        assert_eq!(layout.nth_envv_raw(1), None);
+        assert_eq!(
+            layout.nth_auxv_raw(0),
+            Some(AuxVarRaw::new(AuxVarType::Pagesz, 4096))
+        );
+        assert_eq!(
+            layout.nth_auxv_raw(1),
+            Some(AuxVarRaw::new(AuxVarType::Uid, 1000))
+        );
+        assert_eq!(layout.nth_auxv_raw(2), None);
+        assert_eq!(layout.nth_auxv_raw(usize::MAX), None);
+
+        let mut iter = layout.auxv_raw_iter();
+        assert_eq!(iter.nth(1), Some(AuxVarRaw::new(AuxVarType::Uid, 1000)));
+        assert_eq!(iter.len(), 0);
+    }
+
+    #[test]
+    fn test_auxv_iter_immediate() {
+        use crate::AuxVarType;
+
+        // AT_PAGESZ, AT_EXECFN (pointer), AT_UID
+        let words = [0, 0, 0, 6, 4096, 31, 0x1000, 11, 1000, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert!(
+            layout
+                .auxv_iter_immediate()
+                .eq([(AuxVarType::Pagesz, 4096), (AuxVarType::Uid, 1000)])
+        );
+    }
+
+    #[test]
+    fn test_contains_auxv() {
+        use crate::AuxVarType;
+
+        let words = [0, 0, 0, 6, 4096, 23, 1, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert!(layout.contains_auxv(AuxVarType::Secure));
+        assert!(!layout.contains_auxv(AuxVarType::Uid));
+        assert!(!layout.contains_auxv(AuxVarType::Null));
+        assert!(layout.contains_all_auxv(&[AuxVarType::Pagesz, AuxVarType::Secure]));
+        assert!(!layout.contains_all_auxv(&[AuxVarType::Pagesz, AuxVarType::Uid]));
+        assert!(layout.contains_all_auxv(&[]));
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_at_addresses() {
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Base(0x7f00_0000 as *const u8));
+        builder.add_auxv(AuxVar::Entry(0x40_10c0 as *const u8));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.at_base(), Some(0x7f00_0000));
+        assert_eq!(layout.at_entry(), Some(0x40_10c0));
+        assert_eq!(layout.at_sysinfo(), None);
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Sysinfo(0xffff_e400 as *const u8));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.at_sysinfo(), Some(0xffff_e400));
+        assert_eq!(layout.at_base(), None);
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        use crate::{AuxVarType, PatchError};
+
+        // The lookup table of `apply_patch` relies on this.
+        assert!(
+            AuxVarType::variants()
+                .iter()
+                .all(|key| key.val() <= AuxVarType::MinSigStkSz.val())
+        );
+
+        let mut words: [usize; 12] = [1, 0x1000, 0, 0, 6, 4096, 11, 1000, 11, 1001, 0, 0];
+        // SAFETY: Any bit pattern is a valid `u8` and the length matches.
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), size_of_val(&words))
+        };
+        let patches = [(AuxVarType::Uid, 0), (AuxVarType::Pagesz, 65536)];
+        assert_eq!(StackLayoutRef::apply_patch(bytes, &patches), Ok(2));
+
+        let patches = [(AuxVarType::Uid, 42), (AuxVarType::Gid, 0)];
+        assert_eq!(
+            StackLayoutRef::apply_patch(bytes, &patches),
+            Err(PatchError::TypeNotFound(AuxVarType::Gid))
+        );
+        // nothing was patched, only the first entry of a type is patched
+        assert_eq!(words, [1, 0x1000, 0, 0, 6, 65536, 11, 0, 11, 1001, 0, 0]);
+    }
+
+    #[test]
+    fn test_parse_auxv_section_pod() {
+        use crate::{AuxVarRaw, AuxVarType, AuxvCastError};
+
+        let entries = [
+            AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+            AuxVarRaw::new(AuxVarType::Uid, 1000),
+            AuxVarRaw::new(AuxVarType::Null, 0),
+            AuxVarRaw::new(AuxVarType::Gid, 1000),
+        ];
+        let bytes = AuxVarRaw::slice_as_bytes(&entries);
+        assert_eq!(
+            StackLayoutRef::parse_auxv_section_pod(bytes),
+            Ok(&entries[..3])
+        );
+        assert_eq!(
+            StackLayoutRef::parse_auxv_section_pod(&bytes[1..]),
+            Err(AuxvCastError::InvalidLength(bytes.len() - 1))
+        );
+        assert_eq!(
+            StackLayoutRef::parse_auxv_section_pod(&bytes[..2 * size_of::<AuxVarRaw>()]),
+            Err(AuxvCastError::Unterminated)
+        );
+
+        // matches the entry-by-entry parsing of a full layout
+        let words = [0, 0, 0, 6, 4096, 11, 1000, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let auxv = StackLayoutRef::parse_auxv_section_pod(layout.auxv_entries_bytes()).unwrap();
+        assert!(layout.auxv_raw_iter().eq(auxv[..2].iter().copied()));
+    }
+
+    #[test]
+    fn test_auxv_section() {
+        use crate::{AuxVarRaw, AuxVarType};
+
+        let words = [0, 0, 0, 6, 4096, 11, 1000, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let section = layout.auxv_section();
+        assert_eq!(section.len(), 2);
+        assert!(!section.is_empty());
+        assert_eq!(section.as_slice(), &layout.auxv_raw_entries()[..2]);
+
+        let mut iter = section.into_iter();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(
+            iter.next_back(),
+            Some(AuxVarRaw::new(AuxVarType::Uid, 1000))
+        );
+        assert_eq!(iter.next(), Some(AuxVarRaw::new(AuxVarType::Pagesz, 4096)));
+        assert_eq!(iter.next(), None);
+
+        let mut count = 0;
+        for entry in &section {
+            assert!(!entry.is_null());
+            count += 1;
+        }
+        assert_eq!(count, 2);
+
+        let words = [0, 0, 0, 0, 0];
+        assert!(StackLayoutRef::from_words(&words).auxv_section().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_auxv_as_proc_format() {
+        use crate::{AuxVar, AuxVarRaw, AuxVarType, StackLayoutBuilder};
+        use std::vec::Vec;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Uid(1000));
+        builder.add_auxv(AuxVar::platform("x86_64"));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        let bytes = layout.auxv_as_proc_format();
+        assert_eq!(bytes.len(), 4 * 2 * size_of::<u64>());
+        let words = bytes
+            .chunks_exact(size_of::<u64>())
+            .map(|word| u64::from_ne_bytes(word.try_into().unwrap()) as usize)
+            .collect::<Vec<_>>();
+        let entries = words
+            .chunks_exact(2)
+            .map(|pair| AuxVarRaw::new(pair[0], pair[1]))
+            .collect::<Vec<_>>();
+        assert_eq!(entries, layout.auxv_raw_entries());
+        assert_eq!(entries[0].key(), Ok(AuxVarType::Pagesz));
+        assert!(entries.last().unwrap().is_null());
+
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(bytes, layout.auxv_entries_bytes());
+    }
+
+    #[test]
+    fn test_auxv_raw_entries() {
+        use crate::{AuxVarRaw, AuxVarType};
+
+        let words = [0, 0, 0, 6, 4096, 11, 1000, 0, 0, 0xdead];
+        let layout = StackLayoutRef::from_words(&words);
+        let entries = layout.auxv_raw_entries();
+        assert_eq!(entries.len(), layout.auxvc() + 1);
+        assert_eq!(
+            entries,
+            [
+                AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+                AuxVarRaw::new(AuxVarType::Uid, 1000),
+                AuxVarRaw::new(AuxVarType::Null, 0),
+            ]
+        );
+        assert!(entries.last().unwrap().is_null());
+    }
+
+    #[test]
+    fn test_argv_envv_raw_entries() {
+        let words = [2, 0x1000, 0x2000, 0, 0x3000, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let argv = layout.argv_raw_entries();
+        assert_eq!(argv.len(), layout.argc() + 1);
+        assert_eq!(argv[layout.argc()], 0);
+        assert_eq!(argv, [0x1000, 0x2000, 0]);
+        assert_eq!(layout.envv_raw_entries(), [0x3000, 0]);
+    }
+
+    #[test]
+    fn test_zip_all() {
+        use crate::{AuxVarType, LayoutEntry, SectionKind};
+        use std::vec::Vec;
+
+        let words = [2, 0x1000, 0x2000, 0, 0x3000, 0, 6, 4096, 31, 0x4000, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let entries = layout.zip_all().collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                LayoutEntry::Argc(2),
+                LayoutEntry::Argv(0, 0x1000 as _),
+                LayoutEntry::Argv(1, 0x2000 as _),
+                LayoutEntry::Null(SectionKind::Argv),
+                LayoutEntry::Envv(0, 0x3000 as _),
+                LayoutEntry::Null(SectionKind::Envv),
+                LayoutEntry::AuxvImmediate(AuxVarType::Pagesz, 4096),
+                LayoutEntry::AuxvPointer(AuxVarType::ExecFn, 0x4000),
+                LayoutEntry::Null(SectionKind::Auxv),
+            ]
+        );
+        // Each word is annotated: auxv entries consist of two words.
+        assert_eq!(entries.len() + 3, words.len());
+    }
+
+    #[test]
+    fn test_argc_signed() {
+        let words = [3_usize, 1, 2, 3, 0, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert_eq!(layout.argc_raw(), 3);
+        assert_eq!(layout.argc_signed(), 3);
+
+        let words = [usize::MAX, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert_eq!(layout.argc_raw(), usize::MAX);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(layout.argc_signed(), i64::MAX);
+        #[cfg(target_pointer_width = "32")]
+        assert_eq!(layout.argc_signed(), i64::from(u32::MAX));
+    }
+
+    #[test]
+    fn test_misaligned() {
+        use crate::{AlignmentError, StackLayoutError};
+
+        let words = vec![0_usize; 4];
+        let bytes = StackLayoutRef::from_words(&words).bytes;
+        let misaligned = &bytes[1..];
+        let err = AlignmentError {
+            addr: misaligned.as_ptr() as usize,
+        };
+
+        assert_eq!(StackLayoutRef::check_alignment(bytes), Ok(()));
+        assert_eq!(StackLayoutRef::check_alignment(misaligned), Err(err));
+        assert_eq!(
+            StackLayoutRef::try_new(misaligned, None).unwrap_err(),
+            StackLayoutError::Misaligned(err)
+        );
+    }
+
+    #[test]
+    #[should_panic = "is not aligned to usize"]
+    fn test_misaligned_panics() {
+        let words = [0_usize; 4];
+        let bytes = StackLayoutRef::from_words(&words).bytes;
+        let _ = StackLayoutRef::new(&bytes[1..], None);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_argv_nth() {
+        use crate::StackLayoutBuilder;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("first");
+        builder.add_argv("second");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        assert_eq!(layout.argv_nth_in_buffer(0), Some(c"first"));
+        assert_eq!(layout.argv_nth_in_buffer(1), Some(c"second"));
+        assert_eq!(layout.argv_nth_in_buffer(2), None);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert_eq!(layout.argv_nth_cstr(1), Some(c"second"));
+            assert_eq!(layout.argv_nth_cstr(2), None);
+        }
+
+        // pointers outside of the buffer are not dereferenced
+        let words = [1, 0x1000, 0, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        assert_eq!(layout.argv_nth_in_buffer(0), None);
+        // the null terminator
+        let words = [1, 0, 0, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        // SAFETY: The null pointer is never dereferenced.
+        assert_eq!(unsafe { layout.argv_nth_cstr(0) }, None);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_exact_size_iterators() {
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("first");
+        builder.add_argv("second");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn(c"/usr/bin/foo".into()));
+        builder.add_auxv(AuxVar::Uid(1000));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        let mut argv = layout.argv_raw_iter();
+        assert_eq!(argv.len(), 2);
+        argv.next();
+        assert_eq!(argv.size_hint(), (1, Some(1)));
+        argv.next();
+        assert_eq!(argv.len(), 0);
+        assert_eq!(argv.next(), None);
+
+        assert_eq!(layout.envv_raw_iter().len(), 1);
+        assert_eq!(layout.auxv_raw_iter().len(), 3);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert_eq!(layout.argv_iter().len(), 2);
+            assert_eq!(layout.envv_iter().len(), 1);
+            assert_eq!(layout.auxv_iter().len(), 3);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "builder", feature = "std"))]
+    fn test_print_auxv() {
+        use crate::{AuxVar, StackLayoutBuilder};
+        use std::string::String;
+        use std::vec::Vec;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Entry(0x1000 as _));
+        builder.add_auxv(AuxVar::Random([0xab; 16]));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        let mut output = Vec::new();
+        // SAFETY: This was created for the address space of this process.
+        for aux in unsafe { layout.auxv_iter() } {
+            StackLayoutRef::write_aux_var(&mut output, &aux).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Pagesz:             4096\n\
+             Entry:              0x1000\n\
+             Random:             abababababababababababababababab\n\
+             ExecFn:             ./prog\n"
+        );
+
+        let immediate = layout.auxv_immediate_vars().collect::<Vec<_>>();
+        assert_eq!(
+            immediate,
+            [AuxVar::Pagesz(4096), AuxVar::Entry(0x1000 as _)]
+        );
+
+        layout.print_auxv_safe();
+        // SAFETY: This was created for the address space of this process.
+        unsafe { layout.print_auxv() };
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_content_hash() {
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let buffer_a = builder.clone().build();
+        let buffer_b = builder.clone().build();
+        assert_ne!(buffer_a.as_ptr(), buffer_b.as_ptr());
+        assert_ne!(buffer_a, buffer_b, "pointers should differ");
+
+        builder.add_argv("--help");
+        let buffer_c = builder.build();
+
+        let hash = |buffer: &[u8]| {
+            let layout = StackLayoutRef::new(buffer, None);
+            // SAFETY: This was created for the address space of this process.
+            unsafe { layout.content_hash() }
+        };
+        assert_eq!(hash(&buffer_a), hash(&buffer_b));
+        assert_ne!(hash(&buffer_a), hash(&buffer_c));
+    }
 
-    // Extracted from a Linux application during startup.
-    #[cfg(target_arch = "x86")]
-    const TEST_DATA_X86: Aligned8<[u8; 804]> = Aligned8([
-        4, 0, 0, 0, 148, 92, 235, 255, 213, 92, 235, 255, 219, 92, 235, 255, 226, 92, 235, 255, 0,
-        0, 0, 0, 232, 92, 235, 255, 85, 93, 235, 255, 167, 93, 235, 255, 200, 93, 235, 255, 254,
-        93, 235, 255, 64, 94, 235, 255, 120, 94, 235, 255, 30, 95, 235, 255, 93, 95, 235, 255, 115,
-        95, 235, 255, 139, 95, 235, 255, 170, 95, 235, 255, 197, 95, 235, 255, 6, 96, 235, 255, 36,
-        96, 235, 255, 60, 96, 235, 255, 86, 96, 235, 255, 112, 96, 235, 255, 138, 96, 235, 255,
-        161, 96, 235, 255, 240, 96, 235, 255, 38, 97, 235, 255, 60, 97, 235, 255, 82, 97, 235, 255,
-        93, 97, 235, 255, 139, 97, 235, 255, 152, 97, 235, 255, 94, 110, 235, 255, 186, 110, 235,
-        255, 203, 110, 235, 255, 224, 110, 235, 255, 176, 111, 235, 255, 55, 114, 235, 255, 151,
-        114, 235, 255, 186, 114, 235, 255, 45, 125, 235, 255, 69, 125, 235, 255, 102, 125, 235,
-        255, 125, 125, 235, 255, 224, 126, 235, 255, 179, 129, 235, 255, 199, 129, 235, 255, 124,
-        131, 235, 255, 171, 131, 235, 255, 16, 132, 235, 255, 116, 132, 235, 255, 228, 132, 235,
-        255, 251, 132, 235, 255, 12, 133, 235, 255, 35, 133, 235, 255, 65, 133, 235, 255, 92, 133,
-        235, 255, 116, 133, 235, 255, 136, 133, 235, 255, 159, 133, 235, 255, 180, 133, 235, 255,
-        205, 133, 235, 255, 225, 133, 235, 255, 176, 135, 235, 255, 209, 135, 235, 255, 25, 136,
-        235, 255, 252, 136, 235, 255, 60, 137, 235, 255, 172, 137, 235, 255, 189, 137, 235, 255,
-        221, 137, 235, 255, 90, 145, 235, 255, 106, 145, 235, 255, 250, 145, 235, 255, 45, 146,
-        235, 255, 63, 146, 235, 255, 170, 146, 235, 255, 213, 146, 235, 255, 122, 147, 235, 255,
-        175, 147, 235, 255, 235, 147, 235, 255, 115, 148, 235, 255, 38, 149, 235, 255, 99, 149,
-        235, 255, 182, 149, 235, 255, 224, 149, 235, 255, 235, 149, 235, 255, 192, 152, 235, 255,
-        202, 152, 235, 255, 241, 152, 235, 255, 42, 154, 235, 255, 147, 154, 235, 255, 169, 154,
-        235, 255, 204, 154, 235, 255, 253, 154, 235, 255, 20, 155, 235, 255, 102, 155, 235, 255,
-        139, 155, 235, 255, 147, 155, 235, 255, 248, 155, 235, 255, 33, 156, 235, 255, 65, 156,
-        235, 255, 112, 156, 235, 255, 134, 156, 235, 255, 154, 156, 235, 255, 191, 156, 235, 255,
-        205, 157, 235, 255, 2, 158, 235, 255, 29, 158, 235, 255, 49, 158, 235, 255, 63, 158, 235,
-        255, 81, 158, 235, 255, 94, 158, 235, 255, 120, 158, 235, 255, 174, 158, 235, 255, 219,
-        160, 235, 255, 25, 162, 235, 255, 51, 162, 235, 255, 11, 172, 235, 255, 34, 172, 235, 255,
-        65, 172, 235, 255, 88, 172, 235, 255, 114, 172, 235, 255, 139, 172, 235, 255, 175, 172,
-        235, 255, 219, 172, 235, 255, 12, 173, 235, 255, 58, 173, 235, 255, 91, 173, 235, 255, 141,
-        173, 235, 255, 162, 173, 235, 255, 202, 173, 235, 255, 25, 174, 235, 255, 42, 174, 235,
-        255, 63, 174, 235, 255, 97, 174, 235, 255, 193, 174, 235, 255, 218, 174, 235, 255, 0, 175,
-        235, 255, 35, 175, 235, 255, 77, 175, 235, 255, 98, 175, 235, 255, 123, 175, 235, 255, 152,
-        175, 235, 255, 0, 0, 0, 0, 32, 0, 0, 0, 176, 117, 245, 247, 33, 0, 0, 0, 0, 112, 245, 247,
-        51, 0, 0, 0, 48, 14, 0, 0, 16, 0, 0, 0, 255, 251, 235, 191, 6, 0, 0, 0, 0, 16, 0, 0, 17, 0,
-        0, 0, 100, 0, 0, 0, 3, 0, 0, 0, 52, 128, 4, 8, 4, 0, 0, 0, 32, 0, 0, 0, 5, 0, 0, 0, 8, 0,
-        0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 201, 170, 4, 8, 11, 0, 0,
-        0, 232, 3, 0, 0, 12, 0, 0, 0, 232, 3, 0, 0, 13, 0, 0, 0, 100, 0, 0, 0, 14, 0, 0, 0, 100, 0,
-        0, 0, 23, 0, 0, 0, 0, 0, 0, 0, 25, 0, 0, 0, 219, 77, 235, 255, 26, 0, 0, 0, 2, 0, 0, 0, 31,
-        0, 0, 0, 183, 175, 235, 255, 15, 0, 0, 0, 235, 77, 235, 255, 27, 0, 0, 0, 28, 0, 0, 0, 28,
-        0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 213, 250, 168, 134, 233, 229,
-        101, 88, 100, 213, 132, 214, 57, 104, 200, 105, 54, 56, 54, 0, 0, 0, 0, 0,
-    ]);
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_process_info() {
+        use crate::{AuxVar, ProcessInfo, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        let layout = builder.clone().build();
+        let layout = StackLayoutRef::new(&layout, None);
+        // SAFETY: This was created for the address space of this process.
+        assert_eq!(unsafe { layout.process_info() }, ProcessInfo::default());
+
+        builder.add_argv("./prog");
+        builder.add_argv("--help");
+        builder.add_auxv(AuxVar::Platform("x86_64".into()));
+        builder.add_auxv(AuxVar::Uid(1000));
+        builder.add_auxv(AuxVar::Gid(100));
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::HwCap(0x1f));
+        builder.add_auxv(AuxVar::HwCap2(0x2));
+        builder.add_auxv(AuxVar::Secure(true));
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        let layout = builder.build();
+        let layout = StackLayoutRef::new(&layout, None);
+
+        // SAFETY: This was created for the address space of this process.
+        let info = unsafe { layout.process_info() };
+        assert_eq!(
+            info,
+            ProcessInfo {
+                program_name: Some(c"./prog"),
+                platform: Some(c"x86_64"),
+                uid: Some(1000),
+                gid: Some(100),
+                page_size: Some(4096),
+                hwcap: Some(0x1f),
+                hwcap2: Some(0x2),
+                is_secure: Some(true),
+                random_bytes: Some([7; 16]),
+            }
+        );
+    }
 
     #[test]
-    #[cfg(target_arch = "x86_64")]
-    fn test_parse_real_data() {
-        let data = TEST_DATA_X86_64.as_ref();
-        let layout = StackLayoutRef::new(data, None);
+    #[cfg(feature = "builder")]
+    fn test_checksum_entries() {
+        use crate::{AuxVar, StackLayoutBuilder};
 
-        assert_eq!(layout.argc(), 4);
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let buffer_a = builder.clone().build_at_address(0x1000);
+        let buffer_b = builder.clone().build_at_address(0x7fff_0000);
+        assert_ne!(buffer_a, buffer_b, "pointers should differ");
 
-        // argv
-        {
-            assert_eq!(
-                layout
-                    .get_slice_argv()
-                    .as_ptr()
-                    .align_offset(align_of::<usize>()),
-                0
-            );
-            assert_eq!(layout.argv_raw_iter().count(), 4);
-            // Just printing uncovers memory errors
-            layout
-                .argv_raw_iter()
-                .enumerate()
-                .for_each(|(i, ptr)| eprintln!("  arg {i:>2}: {ptr:?}"));
+        let checksum = |buffer: &[u8]| StackLayoutRef::new(buffer, None).checksum_entries();
+        assert_eq!(checksum(&buffer_a), checksum(&buffer_b));
+
+        builder.add_auxv(AuxVar::Uid(1000));
+        assert_ne!(
+            checksum(&builder.build_at_address(0x1000)),
+            checksum(&buffer_a)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_split_at_envv() {
+        use crate::{AuxVar, AuxVarType, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--help");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        let (argv, rest) = layout.split_at_envv();
+        assert_eq!(argv.argc(), 2);
+        assert!(argv.raw_iter().eq(layout.argv_raw_iter()));
+        assert_eq!(rest.envc(), 1);
+        assert_eq!(rest.auxvc(), 2);
+        assert!(rest.envv_raw_iter().eq(layout.envv_raw_iter()));
+        assert!(rest.auxv_raw_iter().eq(layout.auxv_raw_iter()));
+        assert_eq!(
+            rest.auxv_raw_iter().next().unwrap().key(),
+            Ok(AuxVarType::Pagesz)
+        );
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(argv.iter().eq([c"./prog", c"--help"]));
+            assert!(rest.envv_iter().eq([c"FOO=bar"]));
+            assert!(rest.auxv_iter().eq(layout.auxv_iter()));
         }
+    }
 
-        // envv
-        {
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_diff() {
+        use super::LayoutDiff;
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Uid(1000));
+        let buffer_a = builder.clone().build();
+        builder.add_envv("BAZ=qux");
+        let buffer_b = builder.build();
+        let layout_a = StackLayoutRef::new(&buffer_a, None);
+        let layout_b = StackLayoutRef::new(&buffer_b, None);
+
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
             assert_eq!(
-                layout
-                    .get_slice_envv()
-                    .as_ptr()
-                    .align_offset(align_of::<usize>()),
-                0
+                layout_a.diff(&layout_b),
+                LayoutDiff {
+                    added_envv: std::vec![c"BAZ=qux"],
+                    ..Default::default()
+                }
             );
-            assert_eq!(layout.envv_raw_iter().count(), 139);
-            // Just printing uncovers memory errors
-            layout
-                .envv_raw_iter()
-                .enumerate()
-                .for_each(|(i, ptr)| eprintln!("  env {i:>2}: {ptr:?}"));
+            assert_eq!(layout_b.diff(&layout_b), LayoutDiff::default());
+            assert!(layout_a.diff(&layout_a).is_empty());
         }
 
-        // auxv
-        {
-            assert_eq!(
-                layout
-                    .get_slice_auxv()
-                    .as_ptr()
-                    .align_offset(align_of::<usize>()),
-                0
-            );
-            // Just printing uncovers memory errors
-            assert_eq!(layout.auxv_raw_iter().count(), 20);
-            layout
-                .auxv_raw_iter()
-                .enumerate()
-                .for_each(|(i, ptr)| eprintln!("  aux {i:>2}: {ptr:?}"));
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./other");
+        builder.add_auxv(AuxVar::Pagesz(65536));
+        builder.add_auxv(AuxVar::Gid(1000));
+        let buffer_c = builder.build();
+        let layout_c = StackLayoutRef::new(&buffer_c, None);
+        // SAFETY: This was created for the address space of this process.
+        let diff = unsafe { layout_a.diff(&layout_c) };
+        assert_eq!(diff.added_argv, [c"./other"]);
+        assert_eq!(diff.removed_argv, [c"./prog"]);
+        assert_eq!(diff.removed_envv, [c"FOO=bar"]);
+        assert_eq!(diff.added_auxv, [AuxVar::Gid(1000)]);
+        assert_eq!(diff.removed_auxv, [AuxVar::Uid(1000)]);
+        assert_eq!(
+            diff.changed_auxv,
+            [(AuxVar::Pagesz(4096), AuxVar::Pagesz(65536))]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_layout_format() {
+        use super::LayoutFormat;
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.layout_format(), LayoutFormat::Standard);
+
+        let buffer = StackLayoutBuilder::new().build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.layout_format(), LayoutFormat::Unknown);
+
+        // The data area of `argv` (the first word) precedes the entries. The
+        // heuristic never dereferences the pointer.
+        let mut words = [0_usize; 7];
+        words[1] = 1;
+        words[2] = words.as_ptr() as usize;
+        let layout = StackLayoutRef::from_words(&words[1..]);
+        assert_eq!(layout.layout_format(), LayoutFormat::DataAreaFirst);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_entries_and_data_size() {
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        assert_eq!(layout.entries_size(), (1 + 2 + 2 + 8) * size_of::<usize>());
+        let data_size = layout.string_data_size();
+        assert_eq!(data_size, 7 + 8 + 16 + 7);
+        assert_eq!(layout.entries_size() + data_size, buffer.len());
+
+        assert_eq!(layout.argv_data_bytes(), 7);
+        assert_eq!(layout.envv_data_bytes(), 8);
+        assert_eq!(layout.auxv_data_bytes(), 16 + 7);
+        assert_eq!(
+            layout.argv_data_bytes() + layout.envv_data_bytes() + layout.auxv_data_bytes(),
+            data_size
+        );
+        assert_eq!(layout.avg_argv_len(), 6);
+        assert_eq!(
+            layout.size_of_argv_data_area()
+                + layout.size_of_envv_data_area()
+                + layout.size_of_auxv_data_area(),
+            layout.string_data_size()
+        );
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("a");
+        builder.add_argv("bcd");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.avg_argv_len(), 2);
+        let buffer = StackLayoutBuilder::new().build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(layout.avg_argv_len(), 0);
+
+        let mut builder = StackLayoutBuilder::new().with_string_pool();
+        builder.add_argv("./prog");
+        builder.add_argv("./prog");
+        let buffer = builder.clone().build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        let data_size = layout.string_data_size();
+        assert_eq!(data_size, 7);
+        assert_eq!(layout.entries_size() + data_size, buffer.len());
+        // the shared string counts once
+        assert_eq!(layout.argv_data_bytes(), 7);
+        assert_eq!(
+            layout.size_of_argv_data_area()
+                + layout.size_of_envv_data_area()
+                + layout.size_of_auxv_data_area(),
+            data_size
+        );
+
+        // no pointers are dereferenced
+        let buffer = builder.build_at_address(0x1000);
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(
+            layout.entries_size() + layout.string_data_size(),
+            buffer.len()
+        );
+        assert_eq!(layout.argv_data_bytes(), 7);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    #[cfg_attr(miri, ignore = "file system access is not supported by miri")]
+    fn test_as_ref_bytes() {
+        use crate::StackLayoutBuilder;
+        use core::borrow::Borrow;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        assert_eq!(AsRef::<[u8]>::as_ref(&layout), &buffer[..]);
+        assert_eq!(Borrow::<[u8]>::borrow(&layout), &buffer[..]);
+
+        let path = std::env::temp_dir().join(std::format!(
+            "linux-libc-auxv-as-ref-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, &layout).unwrap();
+        let content = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(content, &buffer[..]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "builder", feature = "std", unix))]
+    fn test_argv_as_os_strings() {
+        use crate::StackLayoutBuilder;
+        use std::ffi::OsString;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--help");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        // SAFETY: This was created for the address space of this process.
+        let argv = unsafe { layout.argv_as_os_strings() };
+        assert_eq!(argv, [OsString::from("./prog"), OsString::from("--help")]);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_iter_owned() {
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--help");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::platform("x86_64"));
+        builder.add_auxv(AuxVar::Random([7; 16]));
+        let buffer = builder.build();
+
+        let layout = StackLayoutRef::new(&buffer, None);
+        // SAFETY: This was created for the address space of this process.
+        let (argv, envv, auxv) = unsafe {
+            (
+                layout.argv_iter_owned(),
+                layout.envv_iter_owned(),
+                layout.auxv_iter_owned(),
+            )
+        };
+        drop(buffer);
+
+        assert_eq!(argv, ["./prog", "--help"]);
+        assert_eq!(envv, ["FOO=bar"]);
+        assert_eq!(auxv[0], AuxVar::Pagesz(4096));
+        assert!(matches!(&auxv[1], AuxVar::Platform(v) if v.as_bytes() == b"x86_64\0"));
+        assert_eq!(auxv[2], AuxVar::Random([7; 16]));
+    }
+
+    #[test]
+    #[cfg(all(feature = "builder", feature = "std", unix))]
+    fn test_iter_lossy() {
+        use crate::StackLayoutBuilder;
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv_os(OsStr::from_bytes(b"a\xffb")).unwrap();
+        builder.add_envv_os(OsStr::from_bytes(b"K=\xfe")).unwrap();
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter_lossy().eq(["./prog", "a\u{fffd}b"]));
+            assert!(layout.envv_iter_lossy().eq(["K=\u{fffd}"]));
         }
     }
 
     #[test]
-    #[cfg(target_arch = "x86")]
-    fn test_parse_real_data() {
-        let data = TEST_DATA_X86.as_ref();
-        let layout = StackLayoutRef::new(data, None);
+    #[cfg(feature = "builder")]
+    fn test_starts_with() {
+        use crate::StackLayoutBuilder;
 
-        assert_eq!(layout.argc(), 4);
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--verbose");
+        builder.add_argv("--flag-a");
+        builder.add_argv("--flag-b");
+        builder.add_envv("PATH=/usr/bin:JAVA_");
+        builder.add_envv("JAVA_HOME=/opt/java");
+        builder.add_envv("JAVA_OPTS=-Xmx1g");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
 
-        // argv
-        {
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert_eq!(layout.argv_starts_with("--flag-"), Some(c"--flag-a"));
+            assert_eq!(layout.argv_starts_with("./"), Some(c"./prog"));
+            assert_eq!(layout.argv_starts_with("-x"), None);
             assert_eq!(
-                layout
-                    .get_slice_argv()
-                    .as_ptr()
-                    .align_offset(align_of::<usize>()),
-                0
+                layout.envv_starts_with_key("JAVA_"),
+                Some(c"JAVA_HOME=/opt/java")
+            );
+            assert_eq!(
+                layout.envv_starts_with_key("JAVA_O"),
+                Some(c"JAVA_OPTS=-Xmx1g")
             );
+            // Only keys are matched, not values.
+            assert_eq!(layout.envv_starts_with_key("/usr"), None);
+            assert_eq!(layout.envv_starts_with_key("JAVA_HOME="), None);
 
-            // Just printing uncovers memory errors
-            layout
-                .argv_raw_iter()
-                .enumerate()
-                .for_each(|(i, ptr)| eprintln!("  arg {i:>2}: {ptr:?}"));
+            assert!(layout.argv_iter_indexed().eq([
+                (0, c"./prog"),
+                (1, c"--verbose"),
+                (2, c"--flag-a"),
+                (3, c"--flag-b")
+            ]));
+            assert_eq!(
+                layout.envv_iter_indexed().last(),
+                Some((2, c"JAVA_OPTS=-Xmx1g"))
+            );
 
-            assert_eq!(layout.argv_raw_iter().count(), 4);
+            assert!(layout.argv_contains("--verbose"));
+            assert!(!layout.argv_contains("--verb"));
+            assert!(layout.envv_contains_key("JAVA_HOME"));
+            assert!(!layout.envv_contains_key("JAVA_"));
+            assert!(!layout.envv_contains_key("/opt/java"));
         }
+    }
 
-        // envv
-        {
-            assert_eq!(
-                layout
-                    .get_slice_envv()
-                    .as_ptr()
-                    .align_offset(align_of::<usize>()),
-                0
-            );
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_iter_all_strings() {
+        use crate::{AuxVar, StackLayoutBuilder, StringKind};
+        use std::vec::Vec;
 
-            // Just printing uncovers memory errors
-            layout
-                .envv_raw_iter()
-                .enumerate()
-                .for_each(|(i, ptr)| eprintln!("  env {i:>2}: {ptr:?}"));
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("--password=hunter2");
+        builder.add_envv("SECRET=hunter2");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::platform("x86_64"));
+        builder.add_auxv(AuxVar::execfn(c"./prog"));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
 
-            assert_eq!(layout.envv_raw_iter().count(), 139);
+        // SAFETY: This was created for the address space of this process.
+        let strings = unsafe { layout.iter_all_strings() }.collect::<Vec<_>>();
+        assert_eq!(strings.len(), layout.argc() + layout.envc() + 2);
+        assert_eq!(
+            strings,
+            [
+                ("./prog", StringKind::Argv(0)),
+                ("--password=hunter2", StringKind::Argv(1)),
+                ("SECRET=hunter2", StringKind::Envv(0)),
+                ("x86_64", StringKind::AuxvPlatform),
+                ("./prog", StringKind::AuxvExecFn),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", unix))]
+    fn test_envv_as_env_pairs() {
+        use std::ffi::OsString;
+
+        #[repr(C, align(8))]
+        struct Buffer([u8; 96]);
+
+        // Built by hand, as the builder rejects entries without `=`.
+        let mut buffer = Buffer([0; 96]);
+        let word = size_of::<usize>();
+        let data = 8 * word;
+        buffer.0[data..data + 15].copy_from_slice(b"A=b=c\0EMPTY=\0X\0");
+        let addr = buffer.0.as_ptr() as usize + data;
+        let words = [0, 0, addr, addr + 6, addr + 13, 0, 0, 0];
+        for (i, value) in words.into_iter().enumerate() {
+            buffer.0[i * word..(i + 1) * word].copy_from_slice(&value.to_ne_bytes());
         }
+        let layout = StackLayoutRef::new(&buffer.0, None);
 
-        // auxv
-        {
+        // SAFETY: The pointers reference the buffer.
+        let (envv, pairs) = unsafe { (layout.envv_as_os_strings(), layout.envv_as_env_pairs()) };
+        assert_eq!(envv, ["A=b=c", "EMPTY=", "X"]);
+        assert_eq!(
+            pairs,
+            [
+                (OsString::from("A"), OsString::from("b=c")),
+                (OsString::from("EMPTY"), OsString::new()),
+                (OsString::from("X"), OsString::new()),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "builder", feature = "std"))]
+    fn test_format_as_shell_env() {
+        use crate::StackLayoutBuilder;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_envv("PATH=/bin:/usr/bin");
+        builder.add_envv("QUOTE=it's");
+        builder.add_envv("EMPTY=");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_envv("$(rm -rf ~)=x");
+        builder.add_envv("A;B=1");
+        builder.add_envv("1X=2");
+        builder.add_envv("_OK1=3");
+        let hostile_buffer = builder.build();
+        let hostile = StackLayoutRef::new(&hostile_buffer, None);
+
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert_eq!(hostile.format_as_shell_env(), "export _OK1='3'\n");
             assert_eq!(
-                layout
-                    .get_slice_auxv()
-                    .as_ptr()
-                    .align_offset(align_of::<usize>()),
-                0
+                layout.format_as_shell_env(),
+                "export PATH='/bin:/usr/bin'\nexport QUOTE='it'\"'\"'s'\nexport EMPTY=''\n"
+            );
+            assert_eq!(
+                layout.format_as_dotenv(),
+                "PATH=/bin:/usr/bin\nQUOTE=it's\nEMPTY=\n"
             );
+        }
+    }
 
-            // Just printing uncovers memory errors
-            layout
-                .auxv_raw_iter()
-                .enumerate()
-                .for_each(|(i, ptr)| eprintln!("  aux {i:>2}: {ptr:?}"));
-            assert_eq!(layout.auxv_raw_iter().count(), 21);
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_section_bytes() {
+        use crate::{AuxVar, AuxVarRaw, AuxVarType, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("a");
+        builder.add_argv("b");
+        builder.add_envv("C=d");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("e".into()));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        let argv = layout.argv_entries_bytes();
+        let envv = layout.envv_entries_bytes();
+        let auxv = layout.auxv_entries_bytes();
+        let data = layout.data_bytes();
+        assert_eq!(argv.len(), 3 * size_of::<usize>());
+        assert_eq!(envv.len(), 2 * size_of::<usize>());
+        assert_eq!(auxv.len(), 3 * size_of::<AuxVarRaw>());
+        assert_eq!(data, b"e\0a\0b\0C=d\0");
+        assert_eq!(
+            size_of::<usize>() + argv.len() + envv.len() + auxv.len() + data.len(),
+            buffer.len()
+        );
+
+        let auxv = AuxVarRaw::slice_from_bytes(auxv).unwrap();
+        assert_eq!(auxv[0], AuxVarRaw::new(AuxVarType::Pagesz, 4096));
+        assert_eq!(auxv[2].key(), Ok(AuxVarType::Null));
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_iter_sections() {
+        use crate::{AuxVar, LayoutSection, StackLayoutBuilder};
+        use std::vec::Vec;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("a");
+        builder.add_envv("B=c");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        let sections = layout.iter_sections().collect::<Vec<_>>();
+        assert_eq!(sections.len(), 5);
+        assert!(matches!(sections[0], LayoutSection::Argc(_)));
+        assert_eq!(
+            sections[1],
+            LayoutSection::ArgvEntries(layout.argv_entries_bytes())
+        );
+        assert_eq!(sections[4], LayoutSection::DataArea(b"a\0B=c\0"));
+        let bytes = sections
+            .iter()
+            .flat_map(|section| section.bytes())
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, buffer.as_ref());
+
+        let argv = &buffer[size_of::<usize>()..];
+        let layout = StackLayoutRef::new(argv, Some(1));
+        let first = layout.iter_sections().next().unwrap();
+        assert!(matches!(first, LayoutSection::ArgvEntries(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_as_bytes() {
+        use crate::{AuxVar, StackLayoutBuilder};
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        builder.add_auxv(AuxVar::ExecFn("./prog".into()));
+        let total_size = builder.total_size();
+        let buffer = builder.build();
+
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+        assert_eq!(layout.as_bytes().len(), total_size);
+        assert_eq!(layout.as_bytes(), buffer.as_ref());
+        assert_eq!(layout.as_ptr(), buffer.as_ptr());
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_iter_str() {
+        use crate::StackLayoutBuilder;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("./prog");
+        builder.add_argv("grüße 👋");
+        builder.add_envv("GREETING=こんにちは");
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter_str().eq([Ok("./prog"), Ok("grüße 👋")]));
+            assert!(layout.envv_iter_str().eq([Ok("GREETING=こんにちは")]));
         }
     }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_from_words() {
+        use crate::{AuxVar, StackLayoutBuilder};
+        use std::vec::Vec;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("first");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Pagesz(4096));
+        let buffer = builder.build();
+        let words = buffer
+            .chunks(size_of::<usize>())
+            .map(|chunk| {
+                let mut word = [0; size_of::<usize>()];
+                word[..chunk.len()].copy_from_slice(chunk);
+                usize::from_ne_bytes(word)
+            })
+            .collect::<Vec<_>>();
+
+        let expected = StackLayoutRef::new(buffer.as_ref(), None);
+        let layout = StackLayoutRef::from_words(&words);
+        assert_eq!(layout.argc(), 1);
+        assert!(layout.argv_raw_iter().eq(expected.argv_raw_iter()));
+        assert!(layout.envv_raw_iter().eq(expected.envv_raw_iter()));
+        assert!(layout.auxv_raw_iter().eq(expected.auxv_raw_iter()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "builder", feature = "std"))]
+    fn test_read_strings_with_reader() {
+        use crate::{AuxVar, AuxVarType, StackLayoutBuilder};
+        use std::borrow::ToOwned;
+
+        let mut builder = StackLayoutBuilder::new();
+        builder.add_argv("first");
+        builder.add_argv("second");
+        builder.add_envv("FOO=bar");
+        builder.add_auxv(AuxVar::Uid(1000));
+        builder.add_auxv(AuxVar::ExecFn(c"/usr/bin/foo".into()));
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(buffer.as_ref(), None);
+
+        // Simulates the memory of another process. Everything outside the
+        // buffer reads as zero.
+        let begin = buffer.as_ptr() as usize;
+        let reader = |addr: usize, buf: &mut [u8]| {
+            for (i, byte) in buf.iter_mut().enumerate() {
+                *byte = buffer.get(addr + i - begin).copied().unwrap_or(0);
+            }
+            Ok(())
+        };
+
+        let argv = layout.read_argv_strings(reader).unwrap();
+        assert_eq!(argv, [c"first".to_owned(), c"second".to_owned()]);
+        let envv = layout.read_envv_strings(reader).unwrap();
+        assert_eq!(envv, [c"FOO=bar".to_owned()]);
+        let auxv = layout.read_auxv_strings(reader).unwrap();
+        assert_eq!(auxv, [(AuxVarType::ExecFn, c"/usr/bin/foo".to_owned())]);
+
+        let failing_reader = |_addr: usize, _buf: &mut [u8]| Err(std::io::ErrorKind::Other.into());
+        assert!(matches!(
+            layout.read_argv_strings(failing_reader),
+            Err(super::ReadError::Io { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_read_strings_address_overflow() {
+        let addr = usize::MAX - 2;
+        let words = [1, addr, 0, 0, 0, 0];
+        let layout = StackLayoutRef::from_words(&words);
+        let reader = |_addr: usize, buf: &mut [u8]| {
+            buf.fill(b'a');
+            Ok(())
+        };
+        assert!(matches!(
+            layout.read_argv_strings(reader),
+            Err(super::ReadError::AddressOverflow(a)) if a == addr
+        ));
+    }
 }
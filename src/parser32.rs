@@ -0,0 +1,194 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::aux_var::{AuxVarRaw, AuxVarType};
+use crate::util::null_term_iter;
+
+/// Like [`StackLayoutRef`] but for the stack layout of a 32-bit process.
+///
+/// All entries are 32-bit words, independent of the pointer width of the
+/// host. This enables, for example, a 64-bit loader to inspect the stack
+/// layout it prepared for a 32-bit application.
+///
+/// As the pointers belong to another address space, only raw values are
+/// emitted.
+///
+/// [`StackLayoutRef`]: crate::StackLayoutRef
+#[derive(Debug)]
+pub struct StackLayoutRef32<'a> {
+    // Might cover more data than the actual content of the stack layout.
+    bytes: &'a [u8],
+}
+
+impl<'a> StackLayoutRef32<'a> {
+    const WORD_SIZE: usize = size_of::<u32>();
+
+    /// Creates a new view into the stack layout. The first word is `argc`.
+    ///
+    /// The words are read in little-endian byte order. As they are read
+    /// byte-wise, the buffer has no alignment requirements.
+    #[must_use]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns the word at the given index.
+    ///
+    /// # Panics
+    /// Panics if the buffer is too small.
+    fn word(&self, index: usize) -> u32 {
+        let offset = index * Self::WORD_SIZE;
+        u32::from_le_bytes(
+            self.bytes[offset..offset + Self::WORD_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Returns the bytes where the Argument Vector (`argv`) begins.
+    fn get_bytes_argv(&self) -> &'a [u8] {
+        &self.bytes[Self::WORD_SIZE..]
+    }
+
+    /// Returns the bytes where the Environmental Variable Vector (`envv`)
+    /// begins.
+    fn get_bytes_envv(&self) -> &'a [u8] {
+        &self.get_bytes_argv()[(self.argc() + 1/* NULL */) * Self::WORD_SIZE..]
+    }
+
+    /// Returns the bytes where the Auxiliary Vector (`auxv`) begins.
+    fn get_bytes_auxv(&self) -> &'a [u8] {
+        &self.get_bytes_envv()[(self.envc() + 1/* NULL */) * Self::WORD_SIZE..]
+    }
+
+    /// Returns the number of arguments.
+    #[must_use]
+    pub fn argc(&self) -> usize {
+        self.word(0) as usize
+    }
+
+    /// Returns the number of environment variables.
+    #[must_use]
+    pub fn envc(&self) -> usize {
+        self.envv_raw_iter().count()
+    }
+
+    /// Returns the number of auxiliary vector entries.
+    #[must_use]
+    pub fn auxvc(&self) -> usize {
+        self.auxv_raw_iter().count()
+    }
+
+    /// Returns an iterator over the raw argument vector's (`argv`) pointers.
+    pub fn argv_raw_iter(&self) -> impl Iterator<Item = u32> + 'a {
+        null_term_iter(words(self.get_bytes_argv()))
+    }
+
+    /// Returns an iterator over the raw environment vector's (`envv`)
+    /// pointers.
+    pub fn envv_raw_iter(&self) -> impl Iterator<Item = u32> + 'a {
+        null_term_iter(words(self.get_bytes_envv()))
+    }
+
+    /// Returns an iterator over the auxiliary variables vector's (`auxv`)
+    /// entries, widened to [`AuxVarRaw`].
+    ///
+    /// Like for [`StackLayoutRef::auxv_raw_iter`], the iteration stops at the
+    /// [`AuxVarType::Null`] entry or the first unknown key.
+    ///
+    /// [`StackLayoutRef::auxv_raw_iter`]: crate::StackLayoutRef::auxv_raw_iter
+    pub fn auxv_raw_iter(&self) -> impl Iterator<Item = AuxVarRaw> + 'a {
+        pairs(self.get_bytes_auxv())
+            .map(|(key, value)| AuxVarRaw::new(key as usize, value as usize))
+            .take_while(|raw| matches!(raw.key(), Ok(key) if key != AuxVarType::Null))
+    }
+}
+
+/// Iterates the little-endian words of the given bytes. Trailing bytes that
+/// don't form a full word are ignored.
+fn words(bytes: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    bytes
+        .chunks_exact(size_of::<u32>())
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+}
+
+/// Iterates the pairs of little-endian words of the given bytes.
+///
+/// # Panics
+/// Panics if the bytes end within a pair.
+fn pairs(bytes: &[u8]) -> impl Iterator<Item = (u32, u32)> + '_ {
+    bytes.chunks(2 * size_of::<u32>()).map(|pair| {
+        assert_eq!(
+            pair.len(),
+            2 * size_of::<u32>(),
+            "auxv array ended prematurely"
+        );
+        let mut words = words(pair);
+        (words.next().unwrap(), words.next().unwrap())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AuxVarRaw, AuxVarType, StackLayoutRef};
+
+    #[test]
+    fn test_from_words32() {
+        let words = [
+            2, 0x1000, 0x1010, 0, 0x1020, 0, 6, 4096, 11, 1000, 0, 0, 0xdead,
+        ];
+        let layout = StackLayoutRef::from_words32(&words);
+
+        assert_eq!(layout.argc(), 2);
+        assert!(layout.argv_raw_iter().eq([0x1000, 0x1010]));
+        assert_eq!(layout.envc(), 1);
+        assert!(layout.envv_raw_iter().eq([0x1020]));
+        assert_eq!(layout.auxvc(), 2);
+        assert!(layout.auxv_raw_iter().eq([
+            AuxVarRaw::new(AuxVarType::Pagesz, 4096),
+            AuxVarRaw::new(AuxVarType::Uid, 1000)
+        ]));
+    }
+
+    #[test]
+    fn test_new_unaligned_le_bytes() {
+        use crate::StackLayoutRef32;
+
+        let words: [u32; 8] = [1, 0x1000, 0, 0, 6, 4096, 0, 0];
+        // one leading byte so that the words are not aligned
+        let mut bytes = [0_u8; 1 + 8 * 4];
+        for (chunk, word) in bytes[1..].chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        let layout = StackLayoutRef32::new(&bytes[1..]);
+
+        assert_eq!(layout.argc(), 1);
+        assert!(layout.argv_raw_iter().eq([0x1000]));
+        assert_eq!(layout.envc(), 0);
+        assert!(
+            layout
+                .auxv_raw_iter()
+                .eq([AuxVarRaw::new(AuxVarType::Pagesz, 4096)])
+        );
+    }
+}
@@ -0,0 +1,427 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+#[cfg(feature = "builder")]
+use crate::AuxVar;
+use crate::AuxVarType;
+use crate::util::get_null_index;
+use core::ffi::CStr;
+#[cfg(feature = "builder")]
+use core::fmt::{self, Write};
+
+/// Serializer for [`StackLayoutBuilder`] and [`StackLayoutBuilderStatic`].
+///
+/// This type takes care of the _entry area_ and the _data area_ with respect
+/// to a given `target_addr` (base address in target address space). See
+/// [`Self::set_target_addr`].
+///
+/// All strings can contain a NUL byte already. If it is not present, the
+/// serializer will take care of that.
+///
+/// All entries are written as words of `word_size` bytes, which allows to
+/// create layouts for targets with a different pointer width than the host.
+///
+/// With a tracer set by [`Self::set_tracer`], every write is described by a
+/// human-readable line.
+pub(crate) struct StackLayoutSerializer<'a> {
+    buffer: &'a mut [u8],
+    // Size in bytes of a word (`argc`, pointers, and auxv keys and values)
+    word_size: usize,
+    // Address of the buffer in the target address space
+    target_addr: usize,
+    // Offset in bytes for writes
+    offset_argv: usize,
+    // Offset in bytes for writes
+    offset_envv: usize,
+    // Offset in bytes for writes
+    offset_auxv: usize,
+    // Offset in bytes for writes
+    offset_argv_data: usize,
+    // Offset in bytes for writes
+    offset_envv_data: usize,
+    // Offset in bytes for writes
+    offset_auxv_data: usize,
+    #[cfg(feature = "builder")]
+    tracer: Option<&'a mut dyn Write>,
+    // The first error of the tracer
+    #[cfg(feature = "builder")]
+    trace_result: fmt::Result,
+}
+
+impl<'a> StackLayoutSerializer<'a> {
+    /// Creates a new builder.
+    ///
+    /// The `auxv` entries [`AuxVarType::Null`] will be added automatically.
+    ///
+    /// # Arguments
+    /// - `word_size`: The size of a word in the target, i.e., `4` or `8`.
+    /// - `len_data_padding`: The number of zero bytes between the entries and
+    ///   the data areas.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        buffer: &'a mut [u8],
+        word_size: usize,
+        len_argv_entries: usize,
+        len_envv_entries: usize,
+        len_auxv_entries: usize,
+        len_data_padding: usize,
+        len_argv_data: usize,
+        len_envv_data: usize,
+        len_auxv_data: usize,
+    ) -> Self {
+        assert!(word_size == 4 || word_size == 8);
+        assert_eq!(buffer.as_ptr().align_offset(word_size), 0);
+
+        let total_size = word_size /* initial argc */ + len_argv_entries + len_envv_entries + len_auxv_entries
+            + len_data_padding + len_argv_data + len_envv_data + len_auxv_data;
+        assert!(buffer.len() >= total_size);
+
+        // These offsets include any necessary NULL entries and NUL bytes.
+        let offset_argv = word_size /* initial argc */;
+        let offset_envv = offset_argv + len_argv_entries;
+        let offset_auxv = offset_envv + len_envv_entries;
+        // auxv data area comes first, then argv, then envv
+        let offset_auxv_data = offset_auxv + len_auxv_entries + len_data_padding;
+        let offset_argv_data = offset_auxv_data + len_auxv_data;
+        let offset_envv_data = offset_argv_data + len_argv_data;
+
+        Self {
+            target_addr: buffer.as_ptr() as usize,
+            buffer,
+            word_size,
+            offset_argv,
+            offset_envv,
+            offset_auxv,
+            offset_argv_data,
+            offset_envv_data,
+            offset_auxv_data,
+            #[cfg(feature = "builder")]
+            tracer: None,
+            #[cfg(feature = "builder")]
+            trace_result: Ok(()),
+        }
+    }
+
+    /// Sets the address of the buffer in the target address space, which is
+    /// used to calculate the pointers into the data areas.
+    ///
+    /// By default, this is the address of the buffer itself.
+    #[cfg(feature = "builder")]
+    pub(crate) const fn set_target_addr(&mut self, target_addr: usize) {
+        self.target_addr = target_addr;
+    }
+
+    /// Sets the tracer that receives a line for every write.
+    #[cfg(feature = "builder")]
+    pub(crate) fn set_tracer(&mut self, tracer: &'a mut dyn Write) {
+        self.tracer = Some(tracer);
+    }
+
+    /// Returns the first error reported by the tracer, if any.
+    #[cfg(feature = "builder")]
+    pub(crate) const fn trace_result(&self) -> fmt::Result {
+        self.trace_result
+    }
+
+    /// Writes a line to the tracer, if set. After the first error, nothing is
+    /// written anymore.
+    #[cfg(feature = "builder")]
+    fn trace(&mut self, args: fmt::Arguments<'_>) {
+        if let Some(tracer) = &mut self.tracer
+            && self.trace_result.is_ok()
+        {
+            self.trace_result = tracer
+                .write_fmt(args)
+                .and_then(|()| tracer.write_char('\n'));
+        }
+    }
+
+    /// Returns the offset in bytes where the next string of the _envv data
+    /// area would be written. As the _envv data area is the last area, this
+    /// is the end of the written layout.
+    #[cfg(feature = "builder")]
+    pub(crate) const fn data_end(&self) -> usize {
+        self.offset_envv_data
+    }
+
+    /// Performs sanity checks ensuring that no offset breaks its boundaries.
+    fn sanity_checks(&self) {
+        assert!(self.offset_argv <= self.offset_envv);
+        assert!(self.offset_envv <= self.offset_auxv);
+        assert!(self.offset_auxv <= self.offset_auxv_data);
+        assert!(self.offset_auxv_data <= self.offset_argv_data);
+        assert!(self.offset_argv_data <= self.offset_envv_data);
+        assert!(self.offset_envv_data <= self.buffer.len());
+    }
+
+    /// Writes `value` as word of `word_size` bytes at `offset`.
+    ///
+    /// # Panics
+    /// Panics if `value` doesn't fit into a word.
+    fn _write_word(buffer: &mut [u8], offset: usize, value: usize, word_size: usize) {
+        let dst = &mut buffer[offset..offset + word_size];
+        if word_size == 4 {
+            let value = u32::try_from(value).expect("value should fit into a 32-bit word");
+            dst.copy_from_slice(&value.to_ne_bytes());
+        } else {
+            dst.copy_from_slice(&(value as u64).to_ne_bytes());
+        }
+    }
+
+    /// Writes bytes to the data area and updates the offset afterward.
+    const fn _write_data_area(buffer: &mut [u8], data: &[u8], data_area_offset: &mut usize) {
+        let src_ptr = data.as_ptr();
+        let dst_ptr = buffer.as_mut_ptr().cast::<u8>();
+        let dst_ptr = unsafe { dst_ptr.add(*data_area_offset) };
+        unsafe {
+            core::ptr::copy_nonoverlapping(src_ptr, dst_ptr, data.len());
+        }
+        *data_area_offset += data.len();
+    }
+
+    /// Writes a null-terminated CStr into the structure, including the
+    /// pointer and the actual data.
+    ///
+    /// Returns the offset of the data in the buffer.
+    fn _write_cstr(
+        buffer: &mut [u8],
+        word_size: usize,
+        target_addr: usize,
+        str: &CStr,
+        entry_offset: &mut usize,
+        data_area_offset: &mut usize,
+    ) -> usize {
+        let data_offset = *data_area_offset;
+
+        // write entry
+        Self::_write_entry(buffer, word_size, target_addr, data_offset, entry_offset);
+
+        // write data
+        Self::_write_data_area(buffer, str.to_bytes(), data_area_offset);
+        // write NUL
+        Self::_write_data_area(buffer, &[0], data_area_offset);
+
+        data_offset
+    }
+
+    /// Writes a pointer to the data at `data_offset` in the buffer.
+    fn _write_entry(
+        buffer: &mut [u8],
+        word_size: usize,
+        target_addr: usize,
+        data_offset: usize,
+        entry_offset: &mut usize,
+    ) {
+        // The address where this will be reachable from a user-perspective.
+        let data_addr = target_addr.wrapping_add(data_offset);
+        Self::_write_word(buffer, *entry_offset, data_addr, word_size);
+        *entry_offset += word_size;
+    }
+
+    /// Writes the `argc` value into the structure.
+    pub(crate) fn write_argc(&mut self, argc: usize) {
+        #[cfg(feature = "builder")]
+        self.trace(format_args!("write_argc({argc}) at offset 0"));
+        Self::_write_word(self.buffer, 0, argc, self.word_size);
+
+        self.sanity_checks();
+    }
+
+    /// Writes an argument into the structure.
+    ///
+    /// Returns the offset of the data in the buffer, which can be referenced
+    /// by [`Self::write_arg_ref`].
+    pub(crate) fn write_arg(&mut self, arg: &CStr) -> usize {
+        #[cfg(feature = "builder")]
+        {
+            let (entry_offset, data_offset) = (self.offset_argv, self.offset_argv_data);
+            self.trace(format_args!(
+                "write_arg({arg:?}) at entry_offset={entry_offset}, data_offset={data_offset}"
+            ));
+        }
+        let data_offset = Self::_write_cstr(
+            self.buffer,
+            self.word_size,
+            self.target_addr,
+            arg,
+            &mut self.offset_argv,
+            &mut self.offset_argv_data,
+        );
+        self.sanity_checks();
+        data_offset
+    }
+
+    /// Writes an argument that references the already written data at
+    /// `data_offset`.
+    #[cfg(feature = "builder")]
+    pub(crate) fn write_arg_ref(&mut self, data_offset: usize) {
+        let entry_offset = self.offset_argv;
+        self.trace(format_args!(
+            "write_arg_ref() at entry_offset={entry_offset}, data_offset={data_offset}"
+        ));
+        Self::_write_entry(
+            self.buffer,
+            self.word_size,
+            self.target_addr,
+            data_offset,
+            &mut self.offset_argv,
+        );
+        self.sanity_checks();
+    }
+
+    /// Writes an environmental variable into the structure.
+    ///
+    /// Returns the offset of the data in the buffer, which can be referenced
+    /// by [`Self::write_env_ref`].
+    pub(crate) fn write_env(&mut self, var: &CStr) -> usize {
+        #[cfg(feature = "builder")]
+        {
+            let (entry_offset, data_offset) = (self.offset_envv, self.offset_envv_data);
+            self.trace(format_args!(
+                "write_env({var:?}) at entry_offset={entry_offset}, data_offset={data_offset}"
+            ));
+        }
+        let data_offset = Self::_write_cstr(
+            self.buffer,
+            self.word_size,
+            self.target_addr,
+            var,
+            &mut self.offset_envv,
+            &mut self.offset_envv_data,
+        );
+
+        self.sanity_checks();
+        data_offset
+    }
+
+    /// Writes an environmental variable that references the already written
+    /// data at `data_offset`.
+    #[cfg(feature = "builder")]
+    pub(crate) fn write_env_ref(&mut self, data_offset: usize) {
+        let entry_offset = self.offset_envv;
+        self.trace(format_args!(
+            "write_env_ref() at entry_offset={entry_offset}, data_offset={data_offset}"
+        ));
+        Self::_write_entry(
+            self.buffer,
+            self.word_size,
+            self.target_addr,
+            data_offset,
+            &mut self.offset_envv,
+        );
+        self.sanity_checks();
+    }
+
+    /// Writes an auxiliary variable into the auxiliary vector.
+    pub(crate) fn write_aux_immediate(&mut self, key: AuxVarType, val: usize) {
+        #[cfg(feature = "builder")]
+        {
+            let entry_offset = self.offset_auxv;
+            self.trace(format_args!(
+                "write_aux_immediate({}, {val:#x}) at entry_offset={entry_offset}",
+                key.name()
+            ));
+        }
+        self._write_aux_entry(key, val);
+    }
+
+    /// Writes an auxiliary variable with a raw type, which may be unknown to
+    /// [`AuxVarType`], into the auxiliary vector.
+    #[cfg(feature = "builder")]
+    pub(crate) fn write_aux_raw(&mut self, key: usize, val: usize) {
+        let entry_offset = self.offset_auxv;
+        self.trace(format_args!(
+            "write_aux_raw({key}, {val:#x}) at entry_offset={entry_offset}"
+        ));
+        self._write_aux_words(key, val);
+        self.sanity_checks();
+    }
+
+    /// Writes the key and the value of an auxiliary variable into the
+    /// auxiliary vector.
+    fn _write_aux_entry(&mut self, key: AuxVarType, val: usize) {
+        self._write_aux_words(key.val(), val);
+    }
+
+    /// Writes the raw key and the value of an auxiliary variable into the
+    /// auxiliary vector.
+    fn _write_aux_words(&mut self, key: usize, val: usize) {
+        Self::_write_word(self.buffer, self.offset_auxv, key, self.word_size);
+        self.offset_auxv += self.word_size;
+        Self::_write_word(self.buffer, self.offset_auxv, val, self.word_size);
+        self.offset_auxv += self.word_size;
+    }
+
+    /// Writes the referenced data of an auxiliary vector into the
+    /// _auxv data area_.
+    pub(crate) fn write_aux_refdata(&mut self, key: AuxVarType, data: &[u8], add_nul_byte: bool) {
+        // The address where this will be reachable from a user-perspective.
+        #[cfg(feature = "builder")]
+        {
+            let (entry_offset, data_offset) = (self.offset_auxv, self.offset_auxv_data);
+            self.trace(format_args!(
+                "write_aux_refdata({}, {} bytes) at entry_offset={entry_offset}, data_offset={data_offset}",
+                key.name(),
+                data.len()
+            ));
+        }
+        let data_addr = self.target_addr.wrapping_add(self.offset_auxv_data);
+        self._write_aux_entry(key, data_addr);
+
+        // write data
+        Self::_write_data_area(self.buffer, data, &mut self.offset_auxv_data);
+
+        // add NUL byte if necessary
+        if add_nul_byte {
+            // assert: If there is a NUL byte in the data, it is only allowed at
+            // the very last position
+            if let Some(pos) = get_null_index(data) {
+                assert_eq!(
+                    pos,
+                    data.len() - 1,
+                    "strings must not contain interim NUL bytes"
+                );
+            }
+
+            if data.last().copied().unwrap() != 0 {
+                // write NUL
+                Self::_write_data_area(self.buffer, &[0], &mut self.offset_auxv_data);
+            }
+        }
+    }
+
+    /// Deconstructs a [`AuxVar`] and writes the corresponding [`AuxVarRaw`]
+    /// into the structure.
+    #[cfg(feature = "builder")]
+    pub(crate) fn write_aux(&mut self, aux: &AuxVar<'_>) {
+        match aux {
+            AuxVar::Platform(v) => self.write_aux_refdata(aux.key(), v.as_bytes(), true),
+            AuxVar::BasePlatform(v) => self.write_aux_refdata(aux.key(), v.as_bytes(), true),
+            AuxVar::Random(v) => self.write_aux_refdata(aux.key(), v, false),
+            AuxVar::ExecFn(v) => self.write_aux_refdata(aux.key(), v.as_bytes(), true),
+            _ => self.write_aux_immediate(aux.key(), aux.value_raw()),
+        }
+
+        self.sanity_checks();
+    }
+}
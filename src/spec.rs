@@ -0,0 +1,351 @@
+/*
+MIT License
+
+Copyright (c) 2025 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Parser of a simple text format that describes a stack layout.
+//!
+//! Each line starts with a section name followed by a colon and a list of
+//! whitespace-separated values:
+//!
+//! ```text
+//! argv: ./prog --flag
+//! env: HOME=/root USER=nobody
+//! auxv: AT_UID=1000 AT_PAGESZ=4096
+//! ```
+//!
+//! Values with whitespace can be quoted with `"` or `'`. Outside of single
+//! quotes, a backslash escapes the next character. Empty lines and lines
+//! starting with `#` are ignored.
+
+use crate::{
+    AddAuxvError, AuxVar, AuxVarRaw, AuxVarType, EnvError, LimitError, StackLayoutBuilder,
+};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Error that is reported if a text specification of a stack layout can't be
+/// parsed by [`StackLayoutBuilder::try_from`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("line {line}: {kind}")]
+pub struct ParseError {
+    /// The 1-based number of the line that failed.
+    pub line: usize,
+    /// The reason of the failure.
+    pub kind: ParseErrorKind,
+}
+
+/// The reason of a [`ParseError`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseErrorKind {
+    /// The line doesn't follow the `<section>: <values>` syntax.
+    #[error("expected `<section>: <values>`")]
+    MissingColon,
+    /// The section is neither `argv`, `env`, nor `auxv`.
+    #[error("unknown section `{0}`")]
+    UnknownSection(String),
+    /// A quote is not closed until the end of the line.
+    #[error("unterminated quote")]
+    UnterminatedQuote,
+    /// The line ends with a backslash.
+    #[error("dangling backslash at the end of the line")]
+    DanglingEscape,
+    /// A value contains a NUL byte.
+    #[error("value `{0}` contains a NUL byte")]
+    InteriorNul(String),
+    /// An environment variable doesn't follow the `key=value` syntax.
+    #[error("environment variable `{0}` doesn't follow the `key=value` syntax")]
+    InvalidEnv(String),
+    /// An `auxv` entry doesn't follow the `AT_NAME=value` syntax.
+    #[error("auxv entry `{0}` doesn't follow the `AT_NAME=value` syntax")]
+    InvalidAuxv(String),
+    /// The name of an `auxv` entry is unknown.
+    #[error("unknown auxv type `{0}`")]
+    UnknownAuxvType(String),
+    /// The value of an `auxv` entry can't be parsed for its type.
+    #[error("invalid value `{value}` for {}", .key.name())]
+    InvalidAuxvValue {
+        /// The type of the entry.
+        key: AuxVarType,
+        /// The value that can't be parsed.
+        value: String,
+    },
+    /// The environment variable was rejected by the builder.
+    #[error(transparent)]
+    Env(#[from] EnvError),
+    /// The argument was rejected by the builder.
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    /// The `auxv` entry was rejected by the builder.
+    #[error(transparent)]
+    Auxv(#[from] AddAuxvError),
+}
+
+impl TryFrom<&str> for StackLayoutBuilder<'static> {
+    type Error = ParseError;
+
+    fn try_from(spec: &str) -> Result<Self, Self::Error> {
+        let mut builder = Self::new();
+        for (i, line) in spec.lines().enumerate() {
+            parse_line(&mut builder, line).map_err(|kind| ParseError { line: i + 1, kind })?;
+        }
+        Ok(builder)
+    }
+}
+
+/// Parses a single line and adds its values to the builder.
+fn parse_line(builder: &mut StackLayoutBuilder<'static>, line: &str) -> Result<(), ParseErrorKind> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(());
+    }
+
+    let (section, values) = line.split_once(':').ok_or(ParseErrorKind::MissingColon)?;
+    let words = split_words(values)?;
+    if let Some(word) = words.iter().find(|word| word.contains('\0')) {
+        return Err(ParseErrorKind::InteriorNul(word.clone()));
+    }
+
+    match section.trim() {
+        "argv" => {
+            for word in words {
+                builder.try_add_argv(word)?;
+            }
+        }
+        "env" => {
+            for word in words {
+                if word.split_once('=').is_none_or(|(key, _)| key.is_empty()) {
+                    return Err(ParseErrorKind::InvalidEnv(word));
+                }
+                builder.try_add_envv(word)?;
+            }
+        }
+        "auxv" => {
+            for word in words {
+                builder.try_add_auxv(parse_aux_var(&word)?)?;
+            }
+        }
+        section => return Err(ParseErrorKind::UnknownSection(section.to_string())),
+    }
+    Ok(())
+}
+
+/// Splits `values` at whitespace while respecting quotes and escapes.
+fn split_words(values: &str) -> Result<Vec<String>, ParseErrorKind> {
+    let mut words = Vec::new();
+    let mut word = None::<String>;
+    let mut chars = values.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\\' => {
+                let c = chars.next().ok_or(ParseErrorKind::DanglingEscape)?;
+                word.get_or_insert_default().push(c);
+            }
+            '\'' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next().ok_or(ParseErrorKind::UnterminatedQuote)? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next().ok_or(ParseErrorKind::UnterminatedQuote)? {
+                        '"' => break,
+                        '\\' => word.push(chars.next().ok_or(ParseErrorKind::UnterminatedQuote)?),
+                        c => word.push(c),
+                    }
+                }
+            }
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Parses an `AT_NAME=value` pair.
+fn parse_aux_var(word: &str) -> Result<AuxVar<'static>, ParseErrorKind> {
+    let (name, value) = word
+        .split_once('=')
+        .ok_or_else(|| ParseErrorKind::InvalidAuxv(word.to_string()))?;
+    let key = AuxVarType::from_name(name)
+        .ok_or_else(|| ParseErrorKind::UnknownAuxvType(name.to_string()))?;
+    let invalid = || ParseErrorKind::InvalidAuxvValue {
+        key,
+        value: value.to_string(),
+    };
+
+    let aux = match key {
+        AuxVarType::Platform => AuxVar::Platform(value.to_string().into()),
+        AuxVarType::BasePlatform => AuxVar::BasePlatform(value.to_string().into()),
+        AuxVarType::ExecFn => AuxVar::ExecFn(value.to_string().into()),
+        AuxVarType::Random => {
+            if value.len() != 32 {
+                return Err(invalid());
+            }
+            let bytes = u128::from_str_radix(value, 16).map_err(|_| invalid())?;
+            AuxVar::Random(bytes.to_be_bytes())
+        }
+        key => {
+            let value = parse_usize(value).ok_or_else(invalid)?;
+            // SAFETY: The value of the remaining types is not in the data
+            // area, so the buffer is never accessed.
+            unsafe { AuxVar::from_raw(&AuxVarRaw::new(key, value), &[]) }
+        }
+    };
+    Ok(aux)
+}
+
+/// Parses a decimal or a `0x`-prefixed hexadecimal integer.
+fn parse_usize(value: &str) -> Option<usize> {
+    value.strip_prefix("0x").map_or_else(
+        || value.parse().ok(),
+        |hex| usize::from_str_radix(hex, 16).ok(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StackLayoutRef;
+
+    #[test]
+    fn test_round_trip() {
+        let spec = "\
+            # comment\n\
+            argv: ./prog --flag \"hello world\"\n\
+            \n\
+            env: HOME=/root USER=nobody 'GREETING=hi there'\n\
+            auxv: AT_UID=1000 AT_PAGESZ=0x1000 AT_SECURE=1\n\
+            auxv: AT_EXECFN=./prog AT_RANDOM=000102030405060708090a0b0c0d0e0f\n";
+        let builder = StackLayoutBuilder::try_from(spec).unwrap();
+
+        let mut expected = StackLayoutBuilder::new();
+        expected.extend_argv(["./prog", "--flag", "hello world"]);
+        expected.extend_envv(["HOME=/root", "USER=nobody", "GREETING=hi there"]);
+        expected.extend([
+            AuxVar::Uid(1000),
+            AuxVar::Pagesz(4096),
+            AuxVar::Secure(true),
+            AuxVar::ExecFn("./prog".to_string().into()),
+            AuxVar::Random(core::array::from_fn(|i| i as u8)),
+        ]);
+        assert_eq!(builder, expected);
+
+        let buffer = builder.build();
+        let layout = StackLayoutRef::new(&buffer, None);
+        // SAFETY: This was created for the address space of this process.
+        unsafe {
+            assert!(layout.argv_iter_str().map(Result::unwrap).eq([
+                "./prog",
+                "--flag",
+                "hello world"
+            ]));
+            assert!(layout.envv_iter_str().map(Result::unwrap).eq([
+                "HOME=/root",
+                "USER=nobody",
+                "GREETING=hi there"
+            ]));
+            let auxv = layout.auxv_iter().collect::<Vec<_>>();
+            assert_eq!(auxv[0], AuxVar::Uid(1000));
+            assert_eq!(auxv[1], AuxVar::Pagesz(4096));
+            assert_eq!(auxv[2], AuxVar::Secure(true));
+            assert_eq!(auxv[4], AuxVar::Random(core::array::from_fn(|i| i as u8)));
+        }
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("  a  b ").unwrap(), ["a", "b"]);
+        assert_eq!(
+            split_words(r#"a\ b "c\"d" 'e\f'"#).unwrap(),
+            ["a b", "c\"d", "e\\f"]
+        );
+        assert_eq!(split_words(r#"x"y z"'!'"#).unwrap(), ["xy z!"]);
+        assert_eq!(split_words("''").unwrap(), [""]);
+        assert_eq!(split_words("'a"), Err(ParseErrorKind::UnterminatedQuote));
+        assert_eq!(split_words("a\\"), Err(ParseErrorKind::DanglingEscape));
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |spec| StackLayoutBuilder::try_from(spec).unwrap_err();
+        assert_eq!(
+            err("argv: a\nfoo"),
+            ParseError {
+                line: 2,
+                kind: ParseErrorKind::MissingColon
+            }
+        );
+        assert_eq!(
+            err("args: a").kind,
+            ParseErrorKind::UnknownSection("args".into())
+        );
+        assert_eq!(
+            err("env: FOO").kind,
+            ParseErrorKind::InvalidEnv("FOO".into())
+        );
+        assert_eq!(
+            err("env: =bar").kind,
+            ParseErrorKind::InvalidEnv("=bar".into())
+        );
+        assert_eq!(
+            err("argv: 'a\0'").kind,
+            ParseErrorKind::InteriorNul("a\0".into())
+        );
+        assert_eq!(
+            err("auxv: AT_UID").kind,
+            ParseErrorKind::InvalidAuxv("AT_UID".into())
+        );
+        assert_eq!(
+            err("auxv: AT_FOO=1").kind,
+            ParseErrorKind::UnknownAuxvType("AT_FOO".into())
+        );
+        assert_eq!(
+            err("auxv: AT_UID=root").kind,
+            ParseErrorKind::InvalidAuxvValue {
+                key: AuxVarType::Uid,
+                value: "root".into()
+            }
+        );
+        assert_eq!(
+            err("auxv: AT_RANDOM=00").kind,
+            ParseErrorKind::InvalidAuxvValue {
+                key: AuxVarType::Random,
+                value: "00".into()
+            }
+        );
+        assert_eq!(
+            err("auxv: AT_NULL=0").kind,
+            ParseErrorKind::Auxv(AddAuxvError::ExplicitNull)
+        );
+        assert_eq!(
+            err("\n\nauxv: AT_UID=root").to_string(),
+            "line 3: invalid value `root` for AT_UID"
+        );
+    }
+}
@@ -22,6 +22,8 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use core::hash::Hasher;
+
 /// Returns the index of the first null byte in the given slice.
 ///
 /// If this returns `None`, the slice doesn't contain a NUL byte.
@@ -36,6 +38,50 @@ pub fn count_bytes_until_null(bytes: &[u8]) -> Option<usize> {
     get_null_index(bytes)
 }
 
+/// Iterates the words of a null-terminated array until the null entry.
+///
+/// # Panics
+/// Panics if the words end before the null entry.
+pub fn null_term_iter<T: Copy + Default + PartialEq>(
+    mut words: impl Iterator<Item = T>,
+) -> impl Iterator<Item = T> {
+    core::iter::from_fn(move || {
+        let word = words
+            .next()
+            .expect("null terminated array ended prematurely");
+        (word != T::default()).then_some(word)
+    })
+}
+
+/// Deterministic 64-bit FNV-1a hasher.
+///
+/// Unlike the hashers of `std`, the result only depends on the written data
+/// and is not randomized per process.
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub const fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,4 +106,16 @@ mod tests {
         assert_eq!(get_null_index(b"abc"), None);
         assert_eq!(count_bytes_until_null(b"abc"), None);
     }
+
+    #[test]
+    fn test_fnv1a_hasher() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = Fnv1aHasher::new();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
 }